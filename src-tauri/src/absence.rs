@@ -0,0 +1,330 @@
+// Absence detection: scheduled workdays, minus holidays, with no entry punch.
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::schedule::{self, ScheduleBook};
+use crate::timesheet::{pad_date, parse_timestamp};
+use crate::timezone;
+use crate::workday::WorkdayBoundary;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashSet;
+
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Serialize)]
+pub struct Holiday {
+    date: String,
+    name: String,
+}
+
+#[derive(Serialize)]
+pub struct EmployeeAbsences {
+    employee_id: String,
+    employee_name: String,
+    expected_days: i64,
+    absent_days: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Absentee {
+    employee_id: String,
+    employee_name: String,
+    expected_start: String,
+}
+
+pub fn get_holidays(conn: &Connection) -> rusqlite::Result<Vec<Holiday>> {
+    let mut stmt = conn.prepare("SELECT date, name FROM holidays ORDER BY date")?;
+    let holidays = stmt
+        .query_map([], |row| {
+            Ok(Holiday {
+                date: row.get(0)?,
+                name: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(holidays)
+}
+
+pub fn add_holiday(conn: &Connection, date: &str, name: &str) -> Result<(), AppError> {
+    if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+        return Err(AppError::validation(tr!("Fecha inválida")));
+    }
+    if name.trim().is_empty() {
+        return Err(AppError::validation(tr!("El nombre del día festivo no puede estar vacío")));
+    }
+    conn.execute(
+        "INSERT INTO holidays (date, name) VALUES (?1, ?2)
+         ON CONFLICT(date) DO UPDATE SET name = excluded.name",
+        params![date, name.trim()],
+    )?;
+    Ok(())
+}
+
+pub fn delete_holiday(conn: &Connection, date: &str) -> Result<(), AppError> {
+    let rows_affected = conn.execute("DELETE FROM holidays WHERE date = ?1", params![date])?;
+    if rows_affected == 0 {
+        return Err(AppError::not_found(tr!("Día festivo no encontrado")));
+    }
+    Ok(())
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::validation(tr!("Fecha inválida: {}", value)))
+}
+
+struct Context {
+    book: ScheduleBook,
+    boundary: WorkdayBoundary,
+    grace_minutes: i64,
+    holidays: HashSet<NaiveDate>,
+    /// (employee_id, workday) pairs with at least one entry.
+    worked: HashSet<(String, NaiveDate)>,
+    employees: Vec<(String, String)>,
+}
+
+fn load_context(
+    conn: &Connection,
+    start: NaiveDate,
+    end: NaiveDate,
+    employee_id: &Option<String>,
+) -> rusqlite::Result<Context> {
+    let boundary = WorkdayBoundary::load(conn)?;
+
+    let holidays = get_holidays(conn)?
+        .into_iter()
+        .filter_map(|h| NaiveDate::parse_from_str(&h.date, "%Y-%m-%d").ok())
+        .collect();
+
+    let start_str = Some(start.format("%Y-%m-%d").to_string());
+    let end_str = Some(end.format("%Y-%m-%d").to_string());
+    let filters = crate::RecordFilters {
+        start_date: pad_date(&start_str, -1),
+        end_date: pad_date(&end_str, 1),
+        employee_id: employee_id.clone(),
+        record_type: Some("entry".to_string()),
+        ..Default::default()
+    };
+    let entries = crate::query_records(conn, &filters)?;
+    let worked = entries
+        .into_iter()
+        .filter_map(|r| {
+            parse_timestamp(&r.timestamp).map(|ts| (r.employee_id, boundary.business_date(ts)))
+        })
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name FROM employees
+         WHERE active = 1 AND deleted_at IS NULL AND (?1 IS NULL OR id = ?1)
+         ORDER BY name",
+    )?;
+    let employees = stmt
+        .query_map(params![employee_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Context {
+        book: ScheduleBook::load(conn)?,
+        boundary,
+        grace_minutes: schedule::grace_minutes(conn)?,
+        holidays,
+        worked,
+        employees,
+    })
+}
+
+/// Absent workdays per active employee between `start_date` and `end_date`
+/// (inclusive). Days after the current workday are never reported, and the
+/// current workday only once the scheduled start plus grace has passed.
+pub fn absences(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    employee_id: &Option<String>,
+) -> Result<Vec<EmployeeAbsences>, AppError> {
+    absences_at(conn, start_date, end_date, employee_id, timezone::now_local())
+}
+
+fn absences_at(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    employee_id: &Option<String>,
+    now: NaiveDateTime,
+) -> Result<Vec<EmployeeAbsences>, AppError> {
+    let start = parse_date(start_date)?;
+    let end = parse_date(end_date)?;
+    if end < start {
+        return Err(AppError::validation(tr!("La fecha final es anterior a la inicial")));
+    }
+    if (end - start).num_days() > MAX_RANGE_DAYS {
+        return Err(AppError::validation(tr!("El periodo no puede exceder un año")));
+    }
+
+    let ctx = load_context(conn, start, end, employee_id)?;
+    let today = ctx.boundary.business_date(now);
+
+    let mut result = Vec::new();
+    for (id, name) in &ctx.employees {
+        let mut expected_days = 0;
+        let mut absent_days = Vec::new();
+
+        let mut day = start;
+        while day <= end.min(today) {
+            if let Some(expected) = ctx.book.expected(id, day) {
+                let started = day < today
+                    || now > expected.start + Duration::minutes(ctx.grace_minutes);
+                if !ctx.holidays.contains(&day) && started {
+                    expected_days += 1;
+                    if !ctx.worked.contains(&(id.clone(), day)) {
+                        absent_days.push(day.format("%Y-%m-%d").to_string());
+                    }
+                }
+            }
+            day += Duration::days(1);
+        }
+
+        if expected_days > 0 {
+            result.push(EmployeeAbsences {
+                employee_id: id.clone(),
+                employee_name: name.clone(),
+                expected_days,
+                absent_days,
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+/// Employees scheduled for the current workday who have not checked in yet,
+/// once their start time plus grace has passed.
+pub fn todays_absentees(conn: &Connection) -> rusqlite::Result<Vec<Absentee>> {
+    absentees_at(conn, timezone::now_local())
+}
+
+fn absentees_at(conn: &Connection, now: NaiveDateTime) -> rusqlite::Result<Vec<Absentee>> {
+    let today = WorkdayBoundary::load(conn)?.business_date(now);
+    let ctx = load_context(conn, today, today, &None)?;
+
+    if ctx.holidays.contains(&today) {
+        return Ok(Vec::new());
+    }
+
+    Ok(ctx
+        .employees
+        .into_iter()
+        .filter_map(|(id, name)| {
+            let expected = ctx.book.expected(&id, today)?;
+            let overdue = now > expected.start + Duration::minutes(ctx.grace_minutes);
+            if overdue && !ctx.worked.contains(&(id.clone(), today)) {
+                Some(Absentee {
+                    employee_id: id,
+                    employee_name: name,
+                    expected_start: expected.start.format("%H:%M").to_string(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+    use crate::schedule::ScheduleDay;
+
+    /// Database with Ana and Luis working 09:00-17:00 on weekdays.
+    fn staffed_db() -> Connection {
+        let conn = migrations::test_db();
+        let days: Vec<ScheduleDay> = (0..5)
+            .map(|weekday| ScheduleDay {
+                weekday,
+                start_time: "09:00".to_string(),
+                end_time: "17:00".to_string(),
+            })
+            .collect();
+        for (id, name) in [("001", "Ana"), ("002", "Luis")] {
+            conn.execute("INSERT INTO employees (id, name) VALUES (?1, ?2)", params![id, name])
+                .unwrap();
+            schedule::set_schedule(&conn, id, "2024-01-01", &days).unwrap();
+        }
+        conn
+    }
+
+    fn at(value: &str) -> NaiveDateTime {
+        parse_timestamp(value).unwrap()
+    }
+
+    /// Stores an entry at site wall-clock `timestamp`.
+    fn check_in(conn: &Connection, employee_id: &str, timestamp: &str) {
+        let stored = timezone::format_utc(timezone::to_utc(at(timestamp)).unwrap());
+        conn.execute(
+            "INSERT INTO attendance (employee_id, timestamp, type) VALUES (?1, ?2, 'entry')",
+            params![employee_id, stored],
+        )
+        .unwrap();
+    }
+
+    fn absent_days(conn: &Connection, employee_id: &str) -> Vec<String> {
+        let found = absences_at(
+            conn,
+            "2024-03-04",
+            "2024-03-10",
+            &Some(employee_id.to_string()),
+            at("2024-03-20 12:00:00"),
+        )
+        .unwrap();
+        found.into_iter().flat_map(|a| a.absent_days).collect()
+    }
+
+    fn absentees(conn: &Connection, now: &str) -> Vec<String> {
+        let found = absentees_at(conn, at(now)).unwrap();
+        found.into_iter().map(|a| a.employee_id).collect()
+    }
+
+    #[test]
+    fn attended_days_and_days_off_are_not_absences() {
+        let conn = staffed_db();
+        for day in ["2024-03-04", "2024-03-05", "2024-03-07", "2024-03-08"] {
+            check_in(&conn, "001", &format!("{} 09:00:00", day));
+        }
+        // Wednesday missed; the weekend has no schedule
+        assert_eq!(absent_days(&conn, "001"), ["2024-03-06"]);
+    }
+
+    #[test]
+    fn holidays_are_not_absences() {
+        let conn = staffed_db();
+        add_holiday(&conn, "2024-03-06", "Asueto").unwrap();
+        let days = absent_days(&conn, "002");
+        assert_eq!(days, ["2024-03-04", "2024-03-05", "2024-03-07", "2024-03-08"]);
+        assert!(absentees(&conn, "2024-03-06 12:00:00").is_empty());
+    }
+
+    #[test]
+    fn absentees_are_listed_once_start_and_grace_have_passed() {
+        let conn = staffed_db();
+        check_in(&conn, "001", "2024-03-04 08:55:00");
+        assert!(absentees(&conn, "2024-03-04 09:05:00").is_empty());
+        assert_eq!(absentees(&conn, "2024-03-04 09:06:00"), ["002"]);
+        // Not scheduled on Saturday
+        assert!(absentees(&conn, "2024-03-09 12:00:00").is_empty());
+    }
+
+    #[test]
+    fn inactive_and_deleted_employees_are_not_listed() {
+        let conn = staffed_db();
+        conn.execute_batch(
+            "UPDATE employees SET active = 0 WHERE id = '001';
+             UPDATE employees SET deleted_at = datetime('now') WHERE id = '002';",
+        )
+        .unwrap();
+        assert!(absentees(&conn, "2024-03-04 12:00:00").is_empty());
+        assert!(absent_days(&conn, "001").is_empty());
+        assert!(absent_days(&conn, "002").is_empty());
+    }
+}
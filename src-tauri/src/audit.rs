@@ -0,0 +1,225 @@
+// Audit trail: every mutation of attendance records, employees and badges stores who
+// made it, when, through which command, and the row before and after as JSON.
+// Corrections of timestamps or types and deletions also carry the reason given
+// by the admin. Entries are written in the same transaction as the change they
+// describe.
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::timezone;
+use crate::users::SessionUser;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Entity {
+    Attendance,
+    Employee,
+    Badge,
+}
+
+impl Entity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Entity::Attendance => "attendance",
+            Entity::Employee => "employee",
+            Entity::Badge => "badge",
+        }
+    }
+}
+
+pub struct Change {
+    pub entity: Entity,
+    pub entity_id: String,
+    pub employee_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub actor: String,
+    pub command: String,
+    pub entity: String,
+    pub entity_id: String,
+    pub employee_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reason: Option<String>,
+}
+
+#[derive(Default)]
+pub struct AuditFilter {
+    pub record_id: Option<i64>,
+    pub employee_id: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+pub fn snapshot<T: Serialize>(value: &T) -> Option<Value> {
+    serde_json::to_value(value).ok()
+}
+
+pub fn record(
+    conn: &Connection,
+    actor: &SessionUser,
+    command: &str,
+    change: Change,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log
+             (timestamp, actor_id, actor, command, entity, entity_id, employee_id,
+              before_json, after_json, reason)
+         VALUES (datetime('now'), ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            actor.id,
+            actor.username,
+            command,
+            change.entity.as_str(),
+            change.entity_id,
+            change.employee_id,
+            change.before.map(|v| v.to_string()),
+            change.after.map(|v| v.to_string()),
+            change.reason,
+        ],
+    )?;
+    Ok(())
+}
+
+fn parse_json(value: Option<String>) -> Option<Value> {
+    value.and_then(|v| serde_json::from_str(&v).ok())
+}
+
+/// Rejects a missing or blank justification.
+pub fn require_reason(reason: &Option<String>) -> Result<String, AppError> {
+    match reason.as_deref().map(str::trim) {
+        Some(r) if !r.is_empty() => Ok(r.to_string()),
+        _ => Err(AppError::validation(tr!("Debes indicar el motivo de la corrección"))),
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn day_start(day: NaiveDate) -> String {
+    timezone::format_utc(timezone::boundary_utc(day.and_time(NaiveTime::MIN)))
+}
+
+/// Newest entries first.
+pub fn query(conn: &Connection, filter: &AuditFilter) -> rusqlite::Result<Vec<AuditEntry>> {
+    let mut sql = String::from(
+        "SELECT id, timestamp, actor, command, entity, entity_id, employee_id,
+                before_json, after_json, reason
+         FROM audit_log WHERE 1=1",
+    );
+    let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+
+    if let Some(record_id) = filter.record_id {
+        param_values.push(Box::new(record_id.to_string()));
+        sql.push_str(&format!(
+            " AND entity = 'attendance' AND entity_id = ?{}",
+            param_values.len()
+        ));
+    }
+    if let Some(ref employee_id) = filter.employee_id {
+        param_values.push(Box::new(employee_id.clone()));
+        sql.push_str(&format!(" AND employee_id = ?{}", param_values.len()));
+    }
+    // Dates are calendar days in site time
+    if let Some(start) = filter.start_date.as_deref().and_then(parse_date) {
+        param_values.push(Box::new(day_start(start)));
+        sql.push_str(&format!(" AND timestamp >= ?{}", param_values.len()));
+    }
+    if let Some(end) = filter.end_date.as_deref().and_then(parse_date) {
+        param_values.push(Box::new(day_start(end + Duration::days(1))));
+        sql.push_str(&format!(" AND timestamp < ?{}", param_values.len()));
+    }
+    sql.push_str(" ORDER BY timestamp DESC, id DESC");
+
+    let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+        param_values.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conn.prepare(&sql)?;
+    let entries = stmt
+        .query_map(params_refs.as_slice(), |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                timestamp: timezone::display(&row.get::<_, String>(1)?),
+                actor: row.get(2)?,
+                command: row.get(3)?,
+                entity: row.get(4)?,
+                entity_id: row.get(5)?,
+                employee_id: row.get(6)?,
+                before: parse_json(row.get(7)?),
+                after: parse_json(row.get(8)?),
+                reason: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+/// Every edited attendance record id with its correction reasons, oldest first
+/// and joined with " | " when a record was corrected more than once. `None`
+/// when none of its edits gave a reason, as with notes-only edits.
+pub fn corrections(conn: &Connection) -> rusqlite::Result<HashMap<i64, Option<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT entity_id, reason FROM audit_log
+         WHERE entity = 'attendance' AND command = 'update_record'
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+    })?;
+
+    let mut corrections: HashMap<i64, Option<String>> = HashMap::new();
+    for row in rows {
+        let (entity_id, reason) = row?;
+        let Ok(id) = entity_id.parse::<i64>() else {
+            continue;
+        };
+        let reasons = corrections.entry(id).or_default();
+        match (reasons, reason) {
+            (Some(r), Some(reason)) => {
+                r.push_str(" | ");
+                r.push_str(&reason);
+            }
+            (r @ None, reason) => *r = reason,
+            (Some(_), None) => {}
+        }
+    }
+    Ok(corrections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    #[test]
+    fn every_edited_record_is_listed() {
+        let conn = migrations::test_db();
+        conn.execute_batch(
+            "INSERT INTO audit_log (timestamp, actor, command, entity, entity_id, reason) VALUES
+                ('2024-03-04 10:00:00', 'admin', 'update_record', 'attendance', '1', NULL),
+                ('2024-03-04 10:01:00', 'admin', 'update_record', 'attendance', '2', 'Olvidó checar'),
+                ('2024-03-04 10:02:00', 'admin', 'update_record', 'attendance', '2', NULL),
+                ('2024-03-04 10:03:00', 'admin', 'update_record', 'attendance', '2', 'Hora mal'),
+                ('2024-03-04 10:04:00', 'admin', 'delete_record', 'attendance', '3', 'Duplicado');",
+        )
+        .unwrap();
+
+        let corrections = corrections(&conn).unwrap();
+        assert_eq!(corrections.len(), 2);
+        assert_eq!(corrections[&1], None);
+        assert_eq!(corrections[&2].as_deref(), Some("Olvidó checar | Hora mal"));
+    }
+}
@@ -0,0 +1,130 @@
+// Admin credentials. Passwords are stored only as Argon2 hashes in `users`;
+// databases created by older versions kept a single shared password in
+// `config` and are migrated on startup.
+
+use crate::config;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::users::{self, Role, SessionUser};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::rngs::OsRng;
+use rusqlite::{params, Connection, OptionalExtension};
+
+const LEGACY_HASH_KEY: &str = "admin_password_hash";
+const LEGACY_PASSWORD_KEY: &str = "admin_password";
+/// Password seeded by older versions; it is published in the design doc, so it
+/// is discarded instead of migrated and the first-run setup is shown again.
+const LEGACY_DEFAULT_PASSWORD: &str = "0824";
+/// `config` keys that hold the legacy password, which may still be there in a
+/// database that never finished migrating.
+pub const LEGACY_KEYS: [&str; 2] = [LEGACY_PASSWORD_KEY, LEGACY_HASH_KEY];
+const MIN_PASSWORD_LENGTH: usize = 6;
+
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::internal(tr!("No se pudo proteger la contraseña"), e))
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+pub fn validate_new_password(password: &str) -> Result<(), AppError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::validation(tr!(
+            "La contraseña debe tener al menos {} caracteres",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Moves the single shared password of older versions into an owner account
+/// named `admin`. Plaintext passwords are hashed on the way.
+pub fn migrate_legacy_password(conn: &Connection) -> Result<(), AppError> {
+    let plain = config::get(conn, LEGACY_PASSWORD_KEY)?;
+    let hash = config::get(conn, LEGACY_HASH_KEY)?;
+
+    let legacy_hash = match (hash, plain) {
+        (Some(hash), _) => Some(hash),
+        (None, Some(plain)) if plain != LEGACY_DEFAULT_PASSWORD => Some(hash_password(&plain)?),
+        _ => None,
+    };
+
+    if let Some(hash) = legacy_hash {
+        if !users::has_users(conn)? {
+            conn.execute(
+                "INSERT INTO users (username, display_name, password_hash, role)
+                 VALUES ('admin', 'Administrador', ?1, 'owner')",
+                params![hash],
+            )?;
+        }
+    }
+
+    conn.execute(
+        "DELETE FROM config WHERE key IN (?1, ?2)",
+        params![LEGACY_KEYS[0], LEGACY_KEYS[1]],
+    )?;
+    Ok(())
+}
+
+fn load_session_user(conn: &Connection, id: i64) -> Result<SessionUser, AppError> {
+    users::get_session_user(conn, id)?.ok_or_else(|| AppError::not_found(tr!("Usuario no encontrado")))
+}
+
+pub fn login(conn: &Connection, username: &str, password: &str) -> Result<SessionUser, AppError> {
+    let account: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, password_hash FROM users WHERE username = ?1 AND active = 1",
+            params![username.trim()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match account {
+        Some((id, hash)) if verify_password(password, &hash) => load_session_user(conn, id),
+        _ => Err(AppError::auth(tr!("Usuario o contraseña incorrectos"))),
+    }
+}
+
+/// First-run setup: creates the owner account. Refuses once any account exists.
+pub fn setup_owner(
+    conn: &Connection,
+    username: &str,
+    display_name: &str,
+    password: &str,
+) -> Result<SessionUser, AppError> {
+    if users::has_users(conn)? {
+        return Err(AppError::conflict(tr!("La cuenta de administrador ya está configurada")));
+    }
+    let id = users::create_user(conn, username, display_name, password, Role::Owner.as_str())?;
+    load_session_user(conn, id)
+}
+
+pub fn change_password(
+    conn: &Connection,
+    user_id: i64,
+    current_password: &str,
+    new_password: &str,
+) -> Result<(), AppError> {
+    let hash: String = conn
+        .query_row(
+            "SELECT password_hash FROM users WHERE id = ?1",
+            params![user_id],
+            |row| row.get(0),
+        )?;
+    if !verify_password(current_password, &hash) {
+        return Err(AppError::auth(tr!("La contraseña actual es incorrecta")));
+    }
+    users::reset_password(conn, user_id, new_password)
+}
@@ -0,0 +1,341 @@
+// Database backups, taken with SQLite's online backup API so they are
+// consistent while the app keeps running. A snapshot is taken at startup, once
+// a day, before migrations and before permanent deletions, and on demand.
+//
+// Backups go to `backups/` next to the database unless another folder is
+// configured, named `<db>-<YYYYMMDD-HHMMSS>-<kind>.db`. Backups of an
+// encrypted database are encrypted with the same secret. After each backup the
+// rotation keeps the newest backup of each of the last `keep_daily` days and of
+// each of the last `keep_weekly` ISO weeks. Only startup and daily backups are
+// rotated; manual ones and those taken before migrations, bulk changes and
+// restores are restore points and are never rotated out.
+
+use crate::config;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::migrations;
+use crate::recovery;
+use crate::storage;
+use crate::timezone;
+use chrono::{Datelike, NaiveDateTime};
+use rusqlite::{Connection, DatabaseName};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const DIR_KEY: &str = "backup_dir";
+const KEEP_DAILY_KEY: &str = "backup_keep_daily";
+const KEEP_WEEKLY_KEY: &str = "backup_keep_weekly";
+const DEFAULT_DIR: &str = "backups";
+const DEFAULT_KEEP_DAILY: u32 = 7;
+const DEFAULT_KEEP_WEEKLY: u32 = 4;
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    Startup,
+    Daily,
+    Manual,
+    PreMigration,
+    PreBulk,
+    PreRestore,
+}
+
+impl BackupKind {
+    const ALL: [BackupKind; 6] = [
+        BackupKind::Startup,
+        BackupKind::Daily,
+        BackupKind::Manual,
+        BackupKind::PreMigration,
+        BackupKind::PreBulk,
+        BackupKind::PreRestore,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupKind::Startup => "startup",
+            BackupKind::Daily => "daily",
+            BackupKind::Manual => "manual",
+            BackupKind::PreMigration => "pre-migration",
+            BackupKind::PreBulk => "pre-bulk",
+            BackupKind::PreRestore => "pre-restore",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == value)
+    }
+
+    fn rotates(self) -> bool {
+        matches!(self, BackupKind::Startup | BackupKind::Daily)
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct BackupInfo {
+    pub name: String,
+    pub path: String,
+    pub kind: BackupKind,
+    pub created_at: String,
+    pub size: u64,
+    #[serde(skip)]
+    created: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackupSettings {
+    /// Empty means the default folder next to the database.
+    pub dir: String,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+}
+
+fn default_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(DEFAULT_DIR)
+}
+
+fn db_stem(db_path: &Path) -> String {
+    db_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "attendance".to_string())
+}
+
+/// Configured values, or the defaults when the config table cannot be read,
+/// as happens before the first migration.
+pub fn settings(conn: &Connection) -> BackupSettings {
+    let get = |key| config::get(conn, key).ok().flatten();
+    BackupSettings {
+        dir: get(DIR_KEY).unwrap_or_default(),
+        keep_daily: get(KEEP_DAILY_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_KEEP_DAILY),
+        keep_weekly: get(KEEP_WEEKLY_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_KEEP_WEEKLY),
+    }
+}
+
+pub fn save_settings(conn: &Connection, settings: &BackupSettings) -> Result<(), AppError> {
+    if settings.keep_daily == 0 && settings.keep_weekly == 0 {
+        return Err(AppError::validation(tr!("Debe conservarse al menos un respaldo diario o semanal")));
+    }
+    let dir = settings.dir.trim();
+    if !dir.is_empty() && !Path::new(dir).is_absolute() {
+        return Err(AppError::validation(tr!("La carpeta de respaldos debe ser una ruta completa")));
+    }
+    config::set(conn, DIR_KEY, dir)?;
+    config::set(conn, KEEP_DAILY_KEY, &settings.keep_daily.to_string())?;
+    config::set(conn, KEEP_WEEKLY_KEY, &settings.keep_weekly.to_string()).map_err(AppError::from)
+}
+
+fn backup_dir(db_path: &Path, settings: &BackupSettings) -> PathBuf {
+    if settings.dir.trim().is_empty() {
+        default_dir(db_path)
+    } else {
+        PathBuf::from(settings.dir.trim())
+    }
+}
+
+/// Backup folder for a database that is not open, as in recovery mode. The
+/// configured folder is read from the database when it is still readable.
+pub fn dir_for(db_path: &Path, key: Option<&str>) -> PathBuf {
+    match storage::open_read_only(db_path, key) {
+        Ok(conn) => backup_dir(db_path, &settings(&conn)),
+        Err(_) => default_dir(db_path),
+    }
+}
+
+fn parse_name(stem: &str, path: &Path) -> Option<BackupInfo> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let rest = name.strip_prefix(stem)?.strip_prefix('-')?.strip_suffix(".db")?;
+    // "YYYYMMDD-HHMMSS" is 15 characters, followed by "-<kind>"
+    let (stamp, kind) = (rest.get(..15)?, rest.get(16..)?);
+    let created = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+    Some(BackupInfo {
+        path: path.display().to_string(),
+        kind: BackupKind::parse(kind)?,
+        created_at: created.format("%Y-%m-%d %H:%M:%S").to_string(),
+        size: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        created,
+        name,
+    })
+}
+
+/// Backups in `dir` for the database at `db_path`, newest first.
+pub fn list_in(dir: &Path, db_path: &Path) -> Vec<BackupInfo> {
+    let stem = db_stem(db_path);
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| parse_name(&stem, &entry.path()))
+        .collect();
+    backups.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.name.cmp(&a.name)));
+    backups
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<BackupInfo>, AppError> {
+    let db_path = storage::file_path(conn)?;
+    Ok(list_in(&backup_dir(&db_path, &settings(conn)), &db_path))
+}
+
+/// Snapshots the database and applies the rotation.
+pub fn create(conn: &Connection, kind: BackupKind) -> Result<BackupInfo, AppError> {
+    let db_path = storage::file_path(conn)?;
+    let settings = settings(conn);
+    let dir = backup_dir(&db_path, &settings);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| {
+            AppError::database(tr!("No se pudo crear la carpeta de respaldos {}", dir.display()), e)
+        })?;
+
+    let now = timezone::now_local();
+    let name = format!("{}-{}-{}.db", db_stem(&db_path), now.format(STAMP_FORMAT), kind.as_str());
+    let target = dir.join(&name);
+    // Written under a temporary name so an interrupted backup is never listed
+    let partial = dir.join(format!("{}.partial", name));
+    if storage::is_encrypted(&db_path) {
+        // The online backup API cannot write an encrypted copy with its own salt
+        storage::export(conn, &partial, None)
+    } else {
+        conn.backup(DatabaseName::Main, &partial, None).map_err(AppError::from)
+    }
+    .map_err(|e| AppError::database(tr!("No se pudo crear el respaldo"), e))?;
+    std::fs::rename(&partial, &target).map_err(|e| AppError::database(tr!("No se pudo crear el respaldo"), e))?;
+
+    let info = parse_name(&db_stem(&db_path), &target)
+        .ok_or_else(|| AppError::validation(tr!("Nombre de respaldo inválido: {}", name)))?;
+    rotate(&dir, &db_path, &settings);
+    Ok(info)
+}
+
+/// Takes the daily backup unless one was already taken today.
+pub fn ensure_daily(conn: &Connection) -> Result<(), AppError> {
+    let today = timezone::now_local().date();
+    let taken_today = list(conn)?.first().is_some_and(|b| b.created.date() >= today);
+    if !taken_today {
+        create(conn, BackupKind::Daily)?;
+    }
+    Ok(())
+}
+
+fn rotate(dir: &Path, db_path: &Path, settings: &BackupSettings) {
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for backup in list_in(dir, db_path).into_iter().filter(|b| b.kind.rotates()) {
+        let day = backup.created.date();
+        let week = (day.iso_week().year(), day.iso_week().week());
+        let mut keep = false;
+        if days.len() < settings.keep_daily as usize && days.insert(day) {
+            keep = true;
+        }
+        if weeks.len() < settings.keep_weekly as usize && weeks.insert(week) {
+            keep = true;
+        }
+        if !keep {
+            // A backup that cannot be removed now is retried on the next rotation
+            let _ = std::fs::remove_file(&backup.path);
+        }
+    }
+}
+
+/// Checks that a file is a healthy database this build can open.
+pub fn validate(path: &Path, key: Option<&str>) -> Result<(), AppError> {
+    let conn = storage::open_read_only(path, key)
+        .map_err(|e| AppError::database(tr!("No se pudo abrir el respaldo"), e))?;
+    let problems = recovery::check(&conn);
+    if !problems.is_empty() {
+        return Err(AppError::validation(tr!("El respaldo está dañado: {}", problems.join("; "))));
+    }
+    let version = migrations::current_version(&conn)?;
+    if version > migrations::latest_version() {
+        return Err(AppError::conflict(tr!("El respaldo fue creado por una versión más reciente de la aplicación")));
+    }
+    let has_attendance: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'attendance')",
+            [],
+            |row| row.get(0),
+        )?;
+    if !has_attendance {
+        return Err(AppError::validation(tr!("El archivo no es un respaldo de asistencia")));
+    }
+    Ok(())
+}
+
+/// Replaces the live database with the backup called `name`, after validating
+/// it and backing up the current state. The caller runs the migrations again,
+/// since the backup may predate the current schema.
+pub fn restore(conn: &mut Connection, key: Option<&str>, name: &str) -> Result<BackupInfo, AppError> {
+    let backup = list(conn)?
+        .into_iter()
+        .find(|b| b.name == name)
+        .ok_or_else(|| AppError::not_found(tr!("Respaldo no encontrado")))?;
+    validate(Path::new(&backup.path), key)?;
+    create(conn, BackupKind::PreRestore)?;
+
+    let staged = storage::file_path(conn)?.with_extension("db.restoring");
+    std::fs::copy(&backup.path, &staged)
+        .map_err(|e| AppError::database(tr!("No se pudo restaurar el respaldo"), e))?;
+    storage::replace(conn, &staged, key, key)?;
+    Ok(backup)
+}
+
+/// Encrypts the existing plain backups with `secret`, once the database itself
+/// has been encrypted. Returns how many were converted.
+pub fn encrypt_all(conn: &Connection, secret: &str) -> Result<usize, AppError> {
+    let mut converted = 0;
+    for backup in list(conn)? {
+        let path = PathBuf::from(&backup.path);
+        if storage::is_encrypted(&path) {
+            continue;
+        }
+        let staged = path.with_extension("db.encrypting");
+        let plain = Connection::open(&path)?;
+        storage::export(&plain, &staged, Some(secret))
+            .map_err(|e| AppError::database(tr!("No se pudo cifrar el respaldo {}", backup.name), e))?;
+        drop(plain);
+        std::fs::rename(&staged, &path)
+            .map_err(|e| AppError::database(tr!("No se pudo cifrar el respaldo {}", backup.name), e))?;
+        converted += 1;
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_points_survive_a_later_daily_backup() {
+        let dir = std::env::temp_dir().join(format!("backup-rotation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("attendance.db");
+        for name in [
+            "20240304-080000-startup",
+            "20240304-090000-pre-bulk",
+            "20240304-100000-pre-restore",
+            "20240304-110000-manual",
+            "20240304-120000-daily",
+        ] {
+            std::fs::write(dir.join(format!("attendance-{}.db", name)), b"").unwrap();
+        }
+
+        let settings = BackupSettings { dir: String::new(), keep_daily: 1, keep_weekly: 1 };
+        rotate(&dir, &db_path, &settings);
+        let kept: Vec<BackupKind> = list_in(&dir, &db_path).into_iter().map(|b| b.kind).collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            kept == [
+                BackupKind::Daily,
+                BackupKind::Manual,
+                BackupKind::PreRestore,
+                BackupKind::PreBulk,
+            ]
+        );
+    }
+}
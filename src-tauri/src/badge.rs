@@ -0,0 +1,208 @@
+// Badges: codes read at the kiosk by a barcode, QR or RFID reader, which type
+// them in like a keyboard followed by Enter. An employee can have several, a
+// printed card and a key fob for instance, but each code belongs to one
+// employee.
+
+use crate::error::{self, AppError};
+use crate::i18n::tr;
+use crate::timezone;
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+const BADGE_QUERY: &str = "SELECT b.code, b.employee_id, e.name, b.label, b.created_at
+                           FROM badges b LEFT JOIN employees e ON e.id = b.employee_id";
+
+#[derive(Serialize)]
+pub struct Badge {
+    pub code: String,
+    pub employee_id: String,
+    pub employee_name: Option<String>,
+    /// What the code is printed on or stored in, e.g. "Tarjeta" or "Llavero".
+    pub label: Option<String>,
+    pub created_at: String,
+}
+
+fn row_to_badge(row: &rusqlite::Row) -> rusqlite::Result<Badge> {
+    Ok(Badge {
+        code: row.get(0)?,
+        employee_id: row.get(1)?,
+        employee_name: row.get(2)?,
+        label: row.get(3)?,
+        created_at: timezone::display(&row.get::<_, String>(4)?),
+    })
+}
+
+/// Readers may send surrounding whitespace or a trailing carriage return.
+fn normalize(code: &str) -> &str {
+    code.trim()
+}
+
+pub fn get(conn: &Connection, code: &str) -> rusqlite::Result<Option<Badge>> {
+    conn.query_row(
+        &format!("{} WHERE b.code = ?1", BADGE_QUERY),
+        params![normalize(code)],
+        row_to_badge,
+    )
+    .optional()
+}
+
+/// Badges of one employee, or of everyone when `employee_id` is `None`.
+pub fn list(conn: &Connection, employee_id: Option<&str>) -> rusqlite::Result<Vec<Badge>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE ?1 IS NULL OR b.employee_id = ?1 ORDER BY e.name, b.code",
+        BADGE_QUERY
+    ))?;
+    let badges = stmt.query_map(params![employee_id], row_to_badge)?;
+    badges.collect()
+}
+
+pub fn assign(
+    conn: &Connection,
+    employee_id: &str,
+    code: &str,
+    label: Option<&str>,
+) -> Result<Badge, AppError> {
+    let code = normalize(code);
+    if code.is_empty() {
+        return Err(AppError::validation(tr!("El código del gafete no puede estar vacío")));
+    }
+    let employee_exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM employees WHERE id = ?1 AND deleted_at IS NULL)",
+        params![employee_id],
+        |row| row.get(0),
+    )?;
+    if !employee_exists {
+        return Err(AppError::not_found(tr!("Empleado no encontrado")));
+    }
+
+    let label = label.map(str::trim).filter(|l| !l.is_empty());
+    conn.execute(
+        "INSERT INTO badges (code, employee_id, label) VALUES (?1, ?2, ?3)",
+        params![code, employee_id, label],
+    )
+    .map_err(|e| {
+        if !error::is_unique_violation(&e) {
+            return AppError::from(e);
+        }
+        let owner = get(conn, code)
+            .ok()
+            .flatten()
+            .map(|b| b.employee_name.unwrap_or(b.employee_id))
+            .unwrap_or_default();
+        AppError::conflict(tr!("El código ya está asignado a {}", owner))
+    })?;
+
+    get(conn, code)?.ok_or_else(|| AppError::not_found(tr!("Gafete no encontrado")))
+}
+
+/// Removes the badge and returns it as it was.
+pub fn revoke(conn: &Connection, code: &str) -> Result<Badge, AppError> {
+    let badge = get(conn, code)?.ok_or_else(|| AppError::not_found(tr!("Gafete no encontrado")))?;
+    conn.execute("DELETE FROM badges WHERE code = ?1", params![badge.code])?;
+    Ok(badge)
+}
+
+/// The employee's most recently assigned badge, the one printed on cards.
+pub fn latest(conn: &Connection, employee_id: &str) -> rusqlite::Result<Option<Badge>> {
+    conn.query_row(
+        &format!("{} WHERE b.employee_id = ?1 ORDER BY b.created_at DESC, b.rowid DESC LIMIT 1", BADGE_QUERY),
+        params![employee_id],
+        row_to_badge,
+    )
+    .optional()
+}
+
+/// A random numeric code for a badge issued by printing it. Twelve digits
+/// keep the barcode short and are not guessable from the employee ID.
+pub fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..12).map(|_| char::from(b'0' + rng.gen_range(0..10))).collect()
+}
+
+/// ID and name of the active employee a scanned code belongs to.
+pub fn resolve(conn: &Connection, code: &str) -> rusqlite::Result<Option<(String, String)>> {
+    conn.query_row(
+        "SELECT e.id, e.name FROM badges b JOIN employees e ON e.id = b.employee_id
+         WHERE b.code = ?1 AND e.active = 1 AND e.deleted_at IS NULL",
+        params![normalize(code)],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    fn add_employee(conn: &Connection, id: &str, name: &str) {
+        conn.execute("INSERT INTO employees (id, name) VALUES (?1, ?2)", params![id, name])
+            .unwrap();
+    }
+
+    fn code<T>(result: Result<T, AppError>) -> &'static str {
+        result.err().unwrap().code()
+    }
+
+    #[test]
+    fn scanned_code_resolves_to_its_employee() {
+        let conn = migrations::test_db();
+        add_employee(&conn, "001", "Ana");
+        let badge = assign(&conn, "001", " 123456\r", Some(" Tarjeta ")).unwrap();
+        assert_eq!(badge.code, "123456");
+        assert_eq!(badge.label.as_deref(), Some("Tarjeta"));
+        assert_eq!(
+            resolve(&conn, "123456\n").unwrap(),
+            Some(("001".to_string(), "Ana".to_string()))
+        );
+        assert_eq!(resolve(&conn, "999").unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_assignments_are_refused() {
+        let conn = migrations::test_db();
+        add_employee(&conn, "001", "Ana");
+        add_employee(&conn, "002", "Luis");
+        assert_eq!(code(assign(&conn, "001", "  ", None)), "VALIDATION");
+        assert_eq!(code(assign(&conn, "404", "123", None)), "NOT_FOUND");
+        assign(&conn, "001", "123", None).unwrap();
+        assert_eq!(code(assign(&conn, "002", "123", None)), "CONFLICT");
+    }
+
+    #[test]
+    fn inactive_and_deleted_employees_are_not_resolved() {
+        let conn = migrations::test_db();
+        add_employee(&conn, "001", "Ana");
+        add_employee(&conn, "002", "Luis");
+        assign(&conn, "001", "111", None).unwrap();
+        assign(&conn, "002", "222", None).unwrap();
+        conn.execute_batch(
+            "UPDATE employees SET active = 0 WHERE id = '001';
+             UPDATE employees SET deleted_at = datetime('now') WHERE id = '002';",
+        )
+        .unwrap();
+        assert_eq!(resolve(&conn, "111").unwrap(), None);
+        assert_eq!(resolve(&conn, "222").unwrap(), None);
+    }
+
+    #[test]
+    fn latest_is_the_last_assigned() {
+        let conn = migrations::test_db();
+        add_employee(&conn, "001", "Ana");
+        assert!(latest(&conn, "001").unwrap().is_none());
+        assign(&conn, "001", "111", None).unwrap();
+        assign(&conn, "001", "222", None).unwrap();
+        assert_eq!(latest(&conn, "001").unwrap().unwrap().code, "222");
+        revoke(&conn, "222").unwrap();
+        assert_eq!(latest(&conn, "001").unwrap().unwrap().code, "111");
+        assert_eq!(list(&conn, Some("001")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn generated_codes_are_twelve_digits() {
+        let code = generate_code();
+        assert_eq!(code.len(), 12);
+        assert!(code.chars().all(|c| c.is_ascii_digit()));
+    }
+}
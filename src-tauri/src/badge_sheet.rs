@@ -0,0 +1,236 @@
+// Printable badge sheets: ID cards of the standard CR80 size (85.60 x 53.98
+// mm), laid out two by four on letter paper with thin outlines to cut along.
+// Each card shows the employee's name and ID, and their badge code as a Code
+// 128 barcode and a QR code so either kind of reader can scan it.
+
+use crate::code128;
+use crate::error::AppError;
+use crate::i18n::tr;
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, Color, Greyscale, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rect,
+};
+use qrcode::{Color as QrColor, QrCode};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const PAGE_WIDTH: f32 = 215.9;
+const PAGE_HEIGHT: f32 = 279.4;
+const CARD_WIDTH: f32 = 85.6;
+const CARD_HEIGHT: f32 = 53.98;
+const COLUMNS: usize = 2;
+const ROWS: usize = 4;
+/// Space between cards, for cutting.
+const GAP: f32 = 6.0;
+/// Inner margin of a card.
+const PADDING: f32 = 4.0;
+const QR_SIZE: f32 = 24.0;
+/// Modules of blank border a QR code needs to be read.
+const QR_QUIET_ZONE: usize = 4;
+const BARCODE_HEIGHT: f32 = 11.0;
+/// Widest bar module; short codes are not stretched past it.
+const MAX_MODULE: f32 = 0.5;
+/// Characters per name line at the name's font size.
+const NAME_LINE_CHARS: usize = 20;
+
+pub struct Card {
+    pub employee_id: String,
+    pub name: String,
+    pub code: String,
+}
+
+struct Fonts {
+    bold: IndirectFontRef,
+    regular: IndirectFontRef,
+}
+
+/// A card's code in both symbologies.
+struct Codes {
+    bars: Vec<u8>,
+    qr: QrCode,
+}
+
+/// Encodes a card's code, rejecting codes that cannot be printed.
+fn encode(code: &str) -> Result<Codes, AppError> {
+    let bars = code128::encode(code).ok_or_else(|| {
+        AppError::validation(tr!("El código {} no puede imprimirse como código de barras", code))
+    })?;
+    let qr = QrCode::new(code.as_bytes()).map_err(|_| {
+        AppError::validation(tr!("El código {} es demasiado largo para un código QR", code))
+    })?;
+    Ok(Codes { bars, qr })
+}
+
+/// Writes the cards to a PDF at `path`, eight per page.
+pub fn render(cards: &[Card], path: &Path) -> Result<(), AppError> {
+    // Every code is checked before anything is drawn
+    let codes = cards.iter().map(|card| encode(&card.code)).collect::<Result<Vec<_>, _>>()?;
+
+    let (doc, page, layer) =
+        PdfDocument::new(tr!("Gafetes"), Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Gafetes");
+    let fonts = Fonts {
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+    };
+
+    let per_page = COLUMNS * ROWS;
+    let left = (PAGE_WIDTH - COLUMNS as f32 * CARD_WIDTH - (COLUMNS - 1) as f32 * GAP) / 2.0;
+    let top = PAGE_HEIGHT - (PAGE_HEIGHT - ROWS as f32 * CARD_HEIGHT - (ROWS - 1) as f32 * GAP) / 2.0;
+
+    let mut current = doc.get_page(page).get_layer(layer);
+    for (i, (card, codes)) in cards.iter().zip(&codes).enumerate() {
+        if i > 0 && i % per_page == 0 {
+            let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Gafetes");
+            current = doc.get_page(page).get_layer(layer);
+        }
+        let slot = i % per_page;
+        let x = left + (slot % COLUMNS) as f32 * (CARD_WIDTH + GAP);
+        let y = top - (slot / COLUMNS + 1) as f32 * CARD_HEIGHT - (slot / COLUMNS) as f32 * GAP;
+        draw_card(&current, &fonts, card, codes, x, y);
+    }
+
+    doc.save(&mut BufWriter::new(File::create(path)?))?;
+    Ok(())
+}
+
+/// Draws a card with its lower left corner at (`x`, `y`).
+fn draw_card(
+    layer: &PdfLayerReference,
+    fonts: &Fonts,
+    card: &Card,
+    codes: &Codes,
+    x: f32,
+    y: f32,
+) {
+    layer.set_outline_color(Color::Greyscale(Greyscale::new(0.6, None)));
+    layer.set_outline_thickness(0.3);
+    layer.add_rect(
+        Rect::new(Mm(x), Mm(y), Mm(x + CARD_WIDTH), Mm(y + CARD_HEIGHT)).with_mode(PaintMode::Stroke),
+    );
+    layer.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
+
+    let mut line_y = y + CARD_HEIGHT - PADDING - 4.0;
+    for line in name_lines(&card.name) {
+        layer.use_text(line, 11.0, Mm(x + PADDING), Mm(line_y), &fonts.bold);
+        line_y -= 5.0;
+    }
+    layer.use_text(format!("ID: {}", card.employee_id), 9.0, Mm(x + PADDING), Mm(line_y - 1.0), &fonts.regular);
+
+    draw_qr(
+        layer,
+        &codes.qr,
+        x + CARD_WIDTH - PADDING - QR_SIZE,
+        y + CARD_HEIGHT - PADDING - QR_SIZE,
+    );
+
+    draw_barcode(layer, &codes.bars, x + PADDING, y + PADDING + 4.0, CARD_WIDTH - 2.0 * PADDING);
+    layer.use_text(card.code.as_str(), 8.0, Mm(x + PADDING), Mm(y + PADDING), &fonts.regular);
+}
+
+/// Splits the name into at most two lines, cutting the second one short.
+fn name_lines(name: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in name.split_whitespace() {
+        let fits = lines
+            .last()
+            .is_some_and(|line| line.chars().count() + 1 + word.chars().count() <= NAME_LINE_CHARS);
+        if fits {
+            let line = lines.last_mut().unwrap();
+            line.push(' ');
+            line.push_str(word);
+        } else if lines.len() < 2 {
+            lines.push(word.to_string());
+        } else {
+            lines[1].push_str(" …");
+            break;
+        }
+    }
+    for line in &mut lines {
+        if line.chars().count() > NAME_LINE_CHARS {
+            *line = line.chars().take(NAME_LINE_CHARS - 1).chain(['…']).collect();
+        }
+    }
+    lines
+}
+
+/// Draws the bars centered in `width` mm, quiet zones included.
+fn draw_barcode(layer: &PdfLayerReference, bars: &[u8], x: f32, y: f32, width: f32) {
+    let modules: usize = bars.iter().map(|&w| w as usize).sum::<usize>() + 2 * code128::QUIET_ZONE;
+    let module = (width / modules as f32).min(MAX_MODULE);
+    let mut bar_x = x + (width - modules as f32 * module) / 2.0 + code128::QUIET_ZONE as f32 * module;
+    for (i, &w) in bars.iter().enumerate() {
+        let bar_width = w as f32 * module;
+        // Even positions are bars, odd ones spaces
+        if i % 2 == 0 {
+            layer.add_rect(Rect::new(Mm(bar_x), Mm(y), Mm(bar_x + bar_width), Mm(y + BARCODE_HEIGHT)));
+        }
+        bar_x += bar_width;
+    }
+}
+
+/// Draws the QR code in a `QR_SIZE` square with its lower left corner at
+/// (`x`, `y`). Runs of dark modules in a row are drawn as one rectangle.
+fn draw_qr(layer: &PdfLayerReference, qr: &QrCode, x: f32, y: f32) {
+    let width = qr.width();
+    let colors = qr.to_colors();
+    let module = QR_SIZE / (width + 2 * QR_QUIET_ZONE) as f32;
+    let origin = QR_QUIET_ZONE as f32 * module;
+
+    for (row, modules) in colors.chunks(width).enumerate() {
+        // PDF coordinates grow upwards, QR rows downwards
+        let top = y + QR_SIZE - origin - row as f32 * module;
+        let mut col = 0;
+        while col < width {
+            if modules[col] != QrColor::Dark {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < width && modules[col] == QrColor::Dark {
+                col += 1;
+            }
+            let left = x + origin + start as f32 * module;
+            layer.add_rect(Rect::new(
+                Mm(left),
+                Mm(top - module),
+                Mm(left + (col - start) as f32 * module),
+                Mm(top),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_names_stay_on_one_line() {
+        assert_eq!(name_lines("  Ana   López "), ["Ana López"]);
+    }
+
+    #[test]
+    fn long_names_wrap_to_two_lines() {
+        assert_eq!(
+            name_lines("María Fernanda Gutiérrez Ortiz"),
+            ["María Fernanda", "Gutiérrez Ortiz"]
+        );
+    }
+
+    #[test]
+    fn names_past_two_lines_are_cut_short() {
+        let lines = name_lines("María Fernanda Gutiérrez Ortiz de la Vega");
+        assert_eq!(lines, ["María Fernanda", "Gutiérrez Ortiz de …"]);
+        assert!(lines.iter().all(|l| l.chars().count() <= NAME_LINE_CHARS));
+
+        let lines = name_lines("Maximilianoalejandrino Bartolomé");
+        assert_eq!(lines, ["Maximilianoalejandr…", "Bartolomé"]);
+    }
+
+    #[test]
+    fn codes_that_cannot_be_printed_are_refused() {
+        assert!(encode("123456789012").is_ok());
+        assert_eq!(encode("Ñ").err().unwrap().code(), "VALIDATION");
+    }
+}
@@ -0,0 +1,259 @@
+// Clock tampering: punch times come from the kiosk computer's clock, so an
+// employee who sets it back or ahead can fake an arrival or a departure. Before
+// each punch the wall clock is compared with the monotonic clock, which cannot
+// be changed, since the previous punch; the first punch after startup is
+// compared with the newest row already stored instead. Punches taken after a
+// jump are flagged and the jump is logged for admins to review.
+//
+// Sleep and hibernation can make the wall clock run ahead of the monotonic
+// one, so forward jumps are only reported past a generous tolerance.
+
+use crate::timezone;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Backward drift tolerated, for time synchronization corrections.
+const BACKWARD_TOLERANCE_SECONDS: i64 = 2 * 60;
+const FORWARD_TOLERANCE_SECONDS: i64 = 15 * 60;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JumpKind {
+    Backward,
+    Forward,
+}
+
+impl JumpKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            JumpKind::Backward => "backward",
+            JumpKind::Forward => "forward",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "forward" => JumpKind::Forward,
+            _ => JumpKind::Backward,
+        }
+    }
+}
+
+/// The wall clock read `actual` when it should have read about `expected`.
+pub struct ClockJump {
+    kind: JumpKind,
+    expected: DateTime<Utc>,
+    actual: DateTime<Utc>,
+}
+
+impl ClockJump {
+    fn between(expected: DateTime<Utc>, actual: DateTime<Utc>, forward: bool) -> Option<Self> {
+        let drift = (actual - expected).num_seconds();
+        let kind = if drift < -BACKWARD_TOLERANCE_SECONDS {
+            JumpKind::Backward
+        } else if forward && drift > FORWARD_TOLERANCE_SECONDS {
+            JumpKind::Forward
+        } else {
+            return None;
+        };
+        Some(ClockJump {
+            kind,
+            expected,
+            actual,
+        })
+    }
+}
+
+/// Wall-clock and monotonic readings taken at the last punch.
+#[derive(Default)]
+pub struct ClockWatch {
+    last: Option<(DateTime<Utc>, Instant)>,
+}
+
+impl ClockWatch {
+    /// Reads the clocks before a punch and keeps them as the new reference.
+    pub fn check(&mut self, conn: &Connection) -> rusqlite::Result<Option<ClockJump>> {
+        let now = Utc::now();
+        let monotonic = Instant::now();
+
+        if let Some((wall, at)) = self.last.replace((now, monotonic)) {
+            let elapsed = Duration::from_std(monotonic - at).unwrap_or(Duration::zero());
+            return Ok(ClockJump::between(wall + elapsed, now, true));
+        }
+
+        // First punch since startup: rows are written with the same clock, so
+        // none should be newer than now. Whether it was set ahead while the
+        // app was closed cannot be told from them.
+        let newest: Option<String> =
+            conn.query_row("SELECT MAX(created_at) FROM attendance", [], |row| row.get(0))?;
+        Ok(newest
+            .as_deref()
+            .and_then(timezone::parse_utc)
+            .and_then(|newest| ClockJump::between(newest, now, false)))
+    }
+}
+
+/// Logs a jump seen while `employee_id` punched and returns its ID. It is
+/// logged before the punch is written, since `ClockWatch` has already moved
+/// past it; the punch is linked with `link` once it is saved, and a refused
+/// punch leaves the anomaly without a record.
+pub fn log(conn: &Connection, jump: &ClockJump, employee_id: &str) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO clock_anomalies (kind, expected_at, detected_at, employee_id)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            jump.kind.as_str(),
+            timezone::format_utc(jump.expected),
+            timezone::format_utc(jump.actual),
+            employee_id
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Links a logged anomaly to the punch it affected.
+pub fn link(conn: &Connection, anomaly_id: i64, record_id: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE clock_anomalies SET record_id = ?1 WHERE id = ?2",
+        params![record_id, anomaly_id],
+    )?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct ClockAnomaly {
+    id: i64,
+    kind: JumpKind,
+    /// About what the clock should have read.
+    expected_at: String,
+    /// What the clock read, and what the punch was stamped with.
+    detected_at: String,
+    /// `detected_at - expected_at`.
+    drift_seconds: i64,
+    employee_id: String,
+    employee_name: Option<String>,
+    record_id: Option<i64>,
+}
+
+/// All logged jumps, newest first. They are ordered by insertion because
+/// their timestamps are exactly what cannot be trusted.
+pub fn anomalies(conn: &Connection) -> rusqlite::Result<Vec<ClockAnomaly>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.kind, c.expected_at, c.detected_at, c.employee_id, e.name, c.record_id
+         FROM clock_anomalies c LEFT JOIN employees e ON e.id = c.employee_id
+         ORDER BY c.id DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let expected_at: String = row.get(2)?;
+        let detected_at: String = row.get(3)?;
+        let expected = timezone::parse_utc(&expected_at);
+        let actual = timezone::parse_utc(&detected_at);
+        let drift_seconds = match (expected, actual) {
+            (Some(expected), Some(actual)) => (actual - expected).num_seconds(),
+            _ => 0,
+        };
+        Ok(ClockAnomaly {
+            id: row.get(0)?,
+            kind: JumpKind::parse(&row.get::<_, String>(1)?),
+            expected_at: timezone::display(&expected_at),
+            detected_at: timezone::display(&detected_at),
+            drift_seconds,
+            employee_id: row.get(4)?,
+            employee_name: row.get(5)?,
+            record_id: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+    use crate::presence::{self, PunchType};
+
+    fn at(value: &str) -> DateTime<Utc> {
+        timezone::parse_utc(value).unwrap()
+    }
+
+    fn kind(jump: Option<ClockJump>) -> Option<JumpKind> {
+        jump.map(|j| j.kind)
+    }
+
+    fn anomaly_records(conn: &Connection) -> Vec<Option<i64>> {
+        let mut stmt = conn.prepare("SELECT record_id FROM clock_anomalies ORDER BY id").unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    fn backward_jump() -> ClockJump {
+        ClockJump::between(at("2024-03-04 12:00:00"), at("2024-03-04 11:00:00"), true).unwrap()
+    }
+
+    #[test]
+    fn small_drift_is_tolerated() {
+        let expected = at("2024-03-04 12:00:00");
+        assert!(kind(ClockJump::between(expected, at("2024-03-04 11:58:00"), true)).is_none());
+        assert!(kind(ClockJump::between(expected, at("2024-03-04 12:15:00"), true)).is_none());
+    }
+
+    #[test]
+    fn jumps_past_the_tolerance_are_reported() {
+        let expected = at("2024-03-04 12:00:00");
+        assert!(
+            kind(ClockJump::between(expected, at("2024-03-04 11:57:59"), true))
+                == Some(JumpKind::Backward)
+        );
+        assert!(
+            kind(ClockJump::between(expected, at("2024-03-04 12:15:01"), true))
+                == Some(JumpKind::Forward)
+        );
+        // Forward jumps cannot be told apart from downtime without the
+        // monotonic clock
+        assert!(kind(ClockJump::between(expected, at("2024-03-05 12:00:00"), false)).is_none());
+    }
+
+    #[test]
+    fn first_punch_is_compared_with_the_newest_row() {
+        let conn = migrations::test_db();
+        assert!(ClockWatch::default().check(&conn).unwrap().is_none());
+
+        conn.execute(
+            "INSERT INTO attendance (employee_id, type, created_at) VALUES ('001', 'entry', ?1)",
+            params![timezone::format_utc(Utc::now() + Duration::hours(2))],
+        )
+        .unwrap();
+        let mut watch = ClockWatch::default();
+        assert!(kind(watch.check(&conn).unwrap()) == Some(JumpKind::Backward));
+        // From then on the monotonic clock is the reference
+        assert!(watch.check(&conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn flagged_punch_is_linked_to_its_anomaly() {
+        let conn = migrations::test_db();
+        let jump = backward_jump();
+        presence::record_punch(&conn, "001", "Ana", PunchType::Entry, Some(&jump))
+            .ok()
+            .unwrap();
+        let (record_id, flag): (i64, String) = conn
+            .query_row("SELECT id, flag FROM attendance", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(flag, presence::FLAG_CLOCK_ANOMALY);
+        assert_eq!(anomaly_records(&conn), [Some(record_id)]);
+    }
+
+    #[test]
+    fn refused_punch_keeps_its_anomaly() {
+        let conn = migrations::test_db();
+        let jump = backward_jump();
+        assert!(presence::record_punch(&conn, "001", "Ana", PunchType::Exit, Some(&jump)).is_err());
+        assert_eq!(anomaly_records(&conn), [None]);
+        let punches: i64 = conn
+            .query_row("SELECT COUNT(*) FROM attendance", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(punches, 0);
+    }
+}
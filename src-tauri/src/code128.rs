@@ -0,0 +1,109 @@
+// Code 128 barcodes for printed badges. Codes made only of digits, in an even
+// number of at least four, use code set C, which packs two digits per symbol
+// and keeps numeric badges short; anything else printable uses code set B.
+
+/// Bar and space widths, in modules, of each symbol value. 103-105 are the
+/// start symbols for code sets A, B and C, and 106 is the stop symbol.
+const PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+const START_B: u8 = 104;
+const START_C: u8 = 105;
+const STOP: u8 = 106;
+
+/// Quiet zone required on each side, in modules.
+pub const QUIET_ZONE: usize = 10;
+
+/// Widths of the alternating bars and spaces encoding `value`, starting with a
+/// bar, without the quiet zones. `None` when `value` is empty or has
+/// characters outside printable ASCII.
+pub fn encode(value: &str) -> Option<Vec<u8>> {
+    let bytes = value.as_bytes();
+    let (start, values): (u8, Vec<u8>) =
+        if bytes.len() >= 4 && bytes.len().is_multiple_of(2) && bytes.iter().all(u8::is_ascii_digit) {
+            let pairs = bytes.chunks(2).map(|p| (p[0] - b'0') * 10 + (p[1] - b'0'));
+            (START_C, pairs.collect())
+        } else if !bytes.is_empty() && bytes.iter().all(|b| (b' '..=b'~').contains(b)) {
+            (START_B, bytes.iter().map(|b| b - b' ').collect())
+        } else {
+            return None;
+        };
+
+    let weighted: u32 = values
+        .iter()
+        .zip(1..)
+        .map(|(&v, weight)| v as u32 * weight)
+        .sum();
+    let checksum = ((start as u32 + weighted) % 103) as u8;
+
+    let symbols = std::iter::once(start)
+        .chain(values)
+        .chain([checksum, STOP]);
+    Some(
+        symbols
+            .flat_map(|s| PATTERNS[s as usize].bytes().map(|w| w - b'0'))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Symbol values read back from the widths, start and stop included.
+    fn symbols(bars: &[u8]) -> Vec<u8> {
+        let (body, stop) = bars.split_at(bars.len() - 7);
+        let mut symbols: Vec<u8> = body
+            .chunks(6)
+            .map(|chunk| {
+                let pattern: String = chunk.iter().map(|w| char::from(b'0' + w)).collect();
+                PATTERNS.iter().position(|p| *p == pattern).unwrap() as u8
+            })
+            .collect();
+        assert_eq!(stop, [2, 3, 3, 1, 1, 1, 2]);
+        symbols.push(STOP);
+        symbols
+    }
+
+    #[test]
+    fn even_digit_runs_use_set_c() {
+        // (105 + 12 * 1 + 34 * 2) % 103 = 82
+        assert_eq!(symbols(&encode("1234").unwrap()), [START_C, 12, 34, 82, STOP]);
+    }
+
+    #[test]
+    fn other_text_uses_set_b() {
+        // (104 + 17 * 1 + 18 * 2 + 19 * 3) % 103 = 8
+        assert_eq!(symbols(&encode("123").unwrap()), [START_B, 17, 18, 19, 8, STOP]);
+        assert_eq!(symbols(&encode("12").unwrap())[0], START_B);
+        assert_eq!(symbols(&encode("A1B2").unwrap())[0], START_B);
+    }
+
+    #[test]
+    fn every_symbol_is_eleven_modules() {
+        let bars = encode("PJJ123C").unwrap();
+        let modules: usize = bars.iter().map(|&w| w as usize).sum();
+        // Start, seven characters and checksum, plus the 13-module stop
+        assert_eq!(modules, 9 * 11 + 13);
+        assert_eq!(bars.len() % 2, 1, "starts and ends with a bar");
+    }
+
+    #[test]
+    fn unencodable_values_are_refused() {
+        assert!(encode("").is_none());
+        assert!(encode("Ñandú").is_none());
+        assert!(encode("tab\t").is_none());
+    }
+}
@@ -0,0 +1,148 @@
+// Errors returned by commands. Each one carries a stable code the frontend can
+// branch on, a message meant for the user and, for failures below the app
+// logic, the technical details. Serialized as the `AppError` interface in
+// src/types/errors.ts:
+//
+//   { "code": "NOT_FOUND", "message": "Registro no encontrado", "details": null }
+
+use crate::i18n::tr;
+use rusqlite::ffi;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AppError {
+    /// Input rejected before anything was changed.
+    Validation(String),
+    /// The record, employee, account or file does not exist.
+    NotFound(String),
+    /// The change clashes with existing data or the current state.
+    Conflict(String),
+    /// Missing or expired session, wrong credentials or missing permission.
+    Auth(String),
+    /// SQLite or file system failure.
+    Database { message: String, details: Option<String> },
+    /// Failure in the app itself or a library it relies on, such as hashing.
+    Internal { message: String, details: Option<String> },
+}
+
+impl AppError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation(message.into())
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        AppError::NotFound(message.into())
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict(message.into())
+    }
+
+    pub fn auth(message: impl Into<String>) -> Self {
+        AppError::Auth(message.into())
+    }
+
+    pub fn database(message: impl Into<String>, details: impl fmt::Display) -> Self {
+        AppError::Database {
+            message: message.into(),
+            details: Some(details.to_string()),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>, details: impl fmt::Display) -> Self {
+        AppError::Internal {
+            message: message.into(),
+            details: Some(details.to_string()),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation(_) => "VALIDATION",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Auth(_) => "AUTH",
+            AppError::Database { .. } => "DATABASE",
+            AppError::Internal { .. } => "INTERNAL",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Validation(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict(message)
+            | AppError::Auth(message)
+            | AppError::Database { message, .. }
+            | AppError::Internal { message, .. } => message,
+        }
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            AppError::Database { details, .. } | AppError::Internal { details, .. } => {
+                details.as_deref()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.details() {
+            Some(details) => write!(f, "{}: {}", self.message(), details),
+            None => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AppError", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", self.message())?;
+        s.serialize_field("details", &self.details())?;
+        s.end()
+    }
+}
+
+/// Whether an insert or update failed on a UNIQUE or PRIMARY KEY constraint.
+pub fn is_unique_violation(e: &rusqlite::Error) -> bool {
+    matches!(
+        e,
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.extended_code == ffi::SQLITE_CONSTRAINT_UNIQUE
+                || err.extended_code == ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+    )
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        if is_unique_violation(&e) {
+            return AppError::conflict(tr!("Ya existe un registro con esos datos"));
+        }
+        AppError::database(tr!("Error de base de datos"), e)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::database(tr!("Error al acceder al archivo"), e)
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for AppError {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        AppError::internal(tr!("No se pudo generar el archivo de Excel"), e)
+    }
+}
+
+impl From<printpdf::Error> for AppError {
+    fn from(e: printpdf::Error) -> Self {
+        AppError::internal(tr!("No se pudo generar el PDF"), e)
+    }
+}
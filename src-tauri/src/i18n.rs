@@ -0,0 +1,321 @@
+// Message catalog for command responses, errors and export labels. Messages
+// are written in es-MX, the default locale, and double as catalog keys; with
+// en-US selected `tr!` looks up the translation, falling back to the Spanish
+// text for anything missing from the catalog.
+//
+// The locale is stored in `config` but kept process-wide once loaded, since
+// errors are built in modules that never see the app state, and in recovery
+// mode before any database is open.
+
+use crate::config;
+use crate::error::AppError;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+const LOCALE_KEY: &str = "locale";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    #[serde(rename = "es-MX")]
+    EsMx,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::EsMx => "es-MX",
+            Locale::EnUs => "en-US",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "es-MX" => Some(Locale::EsMx),
+            "en-US" => Some(Locale::EnUs),
+            _ => None,
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(Locale::EsMx as u8);
+
+pub fn current() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        x if x == Locale::EnUs as u8 => Locale::EnUs,
+        _ => Locale::EsMx,
+    }
+}
+
+fn activate(locale: Locale) {
+    CURRENT.store(locale as u8, Ordering::Relaxed);
+}
+
+/// Activates the configured locale. An unknown value leaves the default.
+pub fn load(conn: &Connection) -> rusqlite::Result<()> {
+    let locale = config::get(conn, LOCALE_KEY)?
+        .as_deref()
+        .and_then(Locale::parse)
+        .unwrap_or(Locale::EsMx);
+    activate(locale);
+    Ok(())
+}
+
+pub fn save(conn: &Connection, locale: Locale) -> Result<(), AppError> {
+    config::set(conn, LOCALE_KEY, locale.as_str())?;
+    activate(locale);
+    Ok(())
+}
+
+/// The message in the active locale.
+pub fn translate(message: &'static str) -> &'static str {
+    match current() {
+        Locale::EsMx => message,
+        Locale::EnUs => {
+            static CATALOG: OnceLock<HashMap<&str, &str>> = OnceLock::new();
+            CATALOG
+                .get_or_init(|| EN_US.iter().copied().collect())
+                .get(message)
+                .copied()
+                .unwrap_or(message)
+        }
+    }
+}
+
+/// Replaces each `{}` in `template` with the next argument.
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+/// Translates a message and fills in its `{}` placeholders, like `format!`.
+macro_rules! tr {
+    ($message:literal) => {
+        $crate::i18n::translate($message).to_string()
+    };
+    ($message:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill(
+            $crate::i18n::translate($message),
+            &[$(&$arg as &dyn ::std::fmt::Display),+],
+        )
+    };
+}
+pub(crate) use tr;
+
+const EN_US: &[(&str, &str)] = &[
+    // Punches
+    ("Entrada", "Entry"),
+    ("Salida", "Exit"),
+    ("Entrada registrada", "Entry recorded"),
+    ("Salida registrada", "Exit recorded"),
+    ("Entrada faltante generada automáticamente", "Missing entry generated automatically"),
+    ("Salida faltante generada automáticamente", "Missing exit generated automatically"),
+    ("El empleado ya tiene una entrada sin salida registrada", "The employee already has an entry without an exit"),
+    ("El empleado no tiene una entrada previa registrada", "The employee has no previous entry"),
+    ("{}. Se generó un registro pendiente de revisión", "{}. A record pending review was generated"),
+    ("El siguiente registro del empleado también es una entrada", "The employee's next record is also an entry"),
+    ("El siguiente registro del empleado también es una salida", "The employee's next record is also an exit"),
+    ("Kiosco", "Kiosk"),
+    ("Importación", "Import"),
+    ("Cierre automático", "Auto close"),
+    ("Ingresa tu PIN", "Enter your PIN"),
+    ("PIN incorrecto, te quedan {} intentos", "Wrong PIN, {} attempts left"),
+    (
+        "Demasiados intentos fallidos, intenta de nuevo en {} minutos",
+        "Too many failed attempts, try again in {} minutes",
+    ),
+    (
+        "El empleado no tiene PIN asignado, solicítalo a un administrador",
+        "The employee has no PIN, ask an administrator for one",
+    ),
+    // Records
+    ("Registro manual agregado", "Manual record added"),
+    ("Registro actualizado", "Record updated"),
+    ("Registro eliminado", "Record deleted"),
+    ("Registro restaurado", "Record restored"),
+    ("Registro eliminado definitivamente", "Record permanently deleted"),
+    ("Registro no encontrado", "Record not found"),
+    ("El registro no está en la papelera", "The record is not in the recycle bin"),
+    ("No hay campos para actualizar", "No fields to update"),
+    ("No se pueden registrar horas futuras", "Future times cannot be recorded"),
+    ("Tipo de registro inválido", "Invalid record type"),
+    ("Fecha y hora inválidas, se esperaba AAAA-MM-DD HH:MM", "Invalid date and time, expected YYYY-MM-DD HH:MM"),
+    ("Origen inválido: {}", "Invalid source: {}"),
+    ("Debes indicar el motivo de la corrección", "You must give a reason for the correction"),
+    ("Ya existe un registro con esos datos", "A record with this data already exists"),
+    // Employees
+    ("Empleado creado exitosamente", "Employee created successfully"),
+    ("Empleado actualizado exitosamente", "Employee updated successfully"),
+    ("Empleado eliminado exitosamente", "Employee deleted successfully"),
+    ("Empleado restaurado", "Employee restored"),
+    ("Empleado eliminado definitivamente", "Employee permanently deleted"),
+    ("Empleado no encontrado", "Employee not found"),
+    ("PIN actualizado", "PIN updated"),
+    ("Gafete asignado", "Badge assigned"),
+    ("Gafete revocado", "Badge revoked"),
+    ("Gafete no encontrado", "Badge not found"),
+    ("Gafete no reconocido", "Badge not recognized"),
+    ("Empleado no encontrado o inactivo", "Employee not found or inactive"),
+    ("El código del gafete no puede estar vacío", "The badge code cannot be empty"),
+    ("El código ya está asignado a {}", "The code is already assigned to {}"),
+    ("Gafete impreso", "Printed badge"),
+    ("Gafetes", "Badges"),
+    ("Gafetes_{}.pdf", "Badges_{}.pdf"),
+    ("{} gafetes guardados en {}", "{} badges saved to {}"),
+    ("Selecciona al menos un empleado", "Select at least one employee"),
+    ("La ruta del archivo debe ser una ruta completa", "The file path must be a full path"),
+    (
+        "El código {} no puede imprimirse como código de barras",
+        "The code {} cannot be printed as a barcode",
+    ),
+    ("El código {} es demasiado largo para un código QR", "The code {} is too long for a QR code"),
+    ("PIN eliminado", "PIN removed"),
+    ("El PIN debe tener entre {} y {} dígitos", "The PIN must have between {} and {} digits"),
+    ("El empleado no está en la papelera", "The employee is not in the recycle bin"),
+    ("El ID del empleado no puede estar vacío", "The employee ID cannot be empty"),
+    ("El nombre del empleado no puede estar vacío", "The employee name cannot be empty"),
+    ("Ya existe un empleado con ese ID", "An employee with this ID already exists"),
+    (
+        "Ya existe un empleado con ese ID en la papelera; restáuralo en lugar de crearlo",
+        "An employee with this ID is in the recycle bin; restore it instead of creating it",
+    ),
+    (
+        "El empleado tiene {} registros de asistencia y no puede eliminarse definitivamente",
+        "The employee has {} attendance records and cannot be permanently deleted",
+    ),
+    // Schedules, holidays and settings
+    ("Horario guardado", "Schedule saved"),
+    ("Horario eliminado", "Schedule deleted"),
+    ("Horario no encontrado", "Schedule not found"),
+    ("Fecha de vigencia inválida", "Invalid effective date"),
+    ("Día de la semana inválido o repetido", "Invalid or repeated weekday"),
+    ("Formato de hora inválido, se esperaba HH:MM", "Invalid time format, expected HH:MM"),
+    ("Zona horaria inválida: {}", "Invalid timezone: {}"),
+    (
+        "La hora {} no existe en la zona horaria {} por el cambio de horario",
+        "The time {} does not exist in the {} timezone because of the daylight saving change",
+    ),
+    ("La tolerancia debe estar entre 0 y 240 minutos", "The grace period must be between 0 and 240 minutes"),
+    ("Día festivo guardado", "Holiday saved"),
+    ("Día festivo eliminado", "Holiday deleted"),
+    ("Día festivo no encontrado", "Holiday not found"),
+    ("El nombre del día festivo no puede estar vacío", "The holiday name cannot be empty"),
+    ("Fecha inválida", "Invalid date"),
+    ("Fecha inválida: {}", "Invalid date: {}"),
+    ("La fecha final es anterior a la inicial", "The end date is before the start date"),
+    ("El periodo no puede exceder un año", "The period cannot exceed one year"),
+    ("Configuración actualizada", "Settings updated"),
+    ("El identificador del dispositivo no puede estar vacío", "The device identifier cannot be empty"),
+    // Accounts and sessions
+    ("Usuario {} creado", "User {} created"),
+    ("Usuario actualizado", "User updated"),
+    ("Usuario no encontrado", "User not found"),
+    ("El usuario {} ya existe", "User {} already exists"),
+    ("El nombre de usuario no puede estar vacío", "The username cannot be empty"),
+    ("El nombre de usuario no puede contener espacios", "The username cannot contain spaces"),
+    ("El nombre no puede estar vacío", "The name cannot be empty"),
+    ("Rol inválido: {}", "Invalid role: {}"),
+    ("Debe existir al menos un propietario activo", "There must be at least one active owner"),
+    ("Contraseña actualizada", "Password updated"),
+    ("Contraseña restablecida", "Password reset"),
+    ("La contraseña debe tener al menos {} caracteres", "The password must be at least {} characters long"),
+    ("La contraseña actual es incorrecta", "The current password is incorrect"),
+    ("No se pudo proteger la contraseña", "The password could not be hashed"),
+    ("Usuario o contraseña incorrectos", "Incorrect username or password"),
+    ("La cuenta de administrador ya está configurada", "The administrator account is already set up"),
+    ("No tienes permiso para realizar esta acción", "You do not have permission to perform this action"),
+    ("La sesión expiró, inicia sesión nuevamente", "The session expired, please log in again"),
+    ("Sesión inválida, inicia sesión nuevamente", "Invalid session, please log in again"),
+    // Database, backups and recovery
+    ("Error de base de datos", "Database error"),
+    ("Error al acceder al archivo", "Error accessing the file"),
+    ("No se pudo generar el archivo de Excel", "The Excel file could not be generated"),
+    ("No se pudo generar el PDF", "Could not generate the PDF"),
+    ("No se pudo leer {}", "Could not read {}"),
+    ("Configuración inválida en {}", "Invalid settings in {}"),
+    ("No se pudo migrar la base de datos {}", "Could not migrate the database {}"),
+    ("No se pudo crear el directorio {}", "Could not create the directory {}"),
+    ("No se pudo abrir {}", "Could not open {}"),
+    ("No se pudo reemplazar la base de datos", "Could not replace the database"),
+    ("La base de datos no está guardada en un archivo", "The database is not stored in a file"),
+    (
+        "La base de datos tiene la versión de esquema {} pero esta versión de la aplicación solo admite hasta la {}. Actualiza la aplicación.",
+        "The database has schema version {} but this version of the application only supports up to {}. Update the application.",
+    ),
+    ("No se pudo respaldar la base de datos antes de migrarla", "Could not back up the database before migrating it"),
+    ("Error al aplicar la migración {} ({})", "Error applying migration {} ({})"),
+    ("Clave de cifrado incorrecta", "Incorrect encryption key"),
+    ("La clave de cifrado debe tener al menos {} caracteres", "The encryption key must be at least {} characters long"),
+    ("La base de datos ya está cifrada", "The database is already encrypted"),
+    ("La base de datos no está bloqueada", "The database is not locked"),
+    (
+        "Base de datos cifrada ({} respaldos cifrados). La clave se pedirá al iniciar la aplicación.",
+        "Database encrypted ({} backups encrypted). The key will be requested when the application starts.",
+    ),
+    ("Configuración de respaldos actualizada", "Backup settings updated"),
+    ("Debe conservarse al menos un respaldo diario o semanal", "At least one daily or weekly backup must be kept"),
+    ("La carpeta de respaldos debe ser una ruta completa", "The backup folder must be a full path"),
+    ("No se pudo crear la carpeta de respaldos {}", "Could not create the backup folder {}"),
+    ("No se pudo crear el respaldo", "Could not create the backup"),
+    ("Nombre de respaldo inválido: {}", "Invalid backup name: {}"),
+    ("No se pudo abrir el respaldo", "Could not open the backup"),
+    ("El respaldo está dañado: {}", "The backup is damaged: {}"),
+    ("El respaldo fue creado por una versión más reciente de la aplicación", "The backup was created by a newer version of the application"),
+    ("El archivo no es un respaldo de asistencia", "The file is not an attendance backup"),
+    ("Respaldo no encontrado", "Backup not found"),
+    ("No se pudo restaurar el respaldo", "Could not restore the backup"),
+    ("No se pudo cifrar el respaldo {}", "Could not encrypt the backup {}"),
+    ("Respaldo del {} restaurado. Inicia sesión nuevamente.", "Backup from {} restored. Please log in again."),
+    ("No hay respaldos disponibles", "No backups available"),
+    ("No se pudo apartar la base de datos dañada", "Could not move the damaged database aside"),
+    ("La aplicación no está en modo de recuperación", "The application is not in recovery mode"),
+    ("Referencia inválida en {} (fila {}) hacia {}", "Invalid reference in {} (row {}) to {}"),
+    ("No se encontraron datos recuperables", "No recoverable data was found"),
+    ("{} filas exportadas a {}", "{} rows exported to {}"),
+    ("Clave de recuperación incorrecta", "Incorrect recovery secret"),
+    ("Clave de recuperación guardada", "Recovery secret saved"),
+    (
+        "No se pueden leer las cuentas, deja el usuario vacío e ingresa la clave de recuperación",
+        "The accounts cannot be read, leave the username empty and enter the recovery secret",
+    ),
+    ("No se pudo determinar la ubicación de la base de datos", "Could not determine the database location"),
+    ("No se pudo guardar {}", "Could not save {}"),
+    // Exports
+    ("Asistencia_{}.xlsx", "Attendance_{}.xlsx"),
+    ("Auditoria_{}.xlsx", "Audit_{}.xlsx"),
+    ("Recuperacion_{}.xlsx", "Recovery_{}.xlsx"),
+    ("Empleado ID", "Employee ID"),
+    ("Nombre", "Name"),
+    ("Fecha", "Date"),
+    ("Hora", "Time"),
+    ("Tipo", "Type"),
+    ("Notas", "Notes"),
+    ("Jornada", "Workday"),
+    ("Origen", "Source"),
+    ("Dispositivo", "Device"),
+    ("Corrección", "Correction"),
+    ("Editado", "Edited"),
+    ("Usuario", "User"),
+    ("Comando", "Command"),
+    ("Entidad", "Entity"),
+    ("Registro", "Record"),
+    ("Antes", "Before"),
+    ("Después", "After"),
+    ("Motivo", "Reason"),
+];
@@ -1,14 +1,76 @@
 // Tauri genera este archivo, solo agregas los comandos
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use chrono::Local;
-use rusqlite::{params, Connection};
+mod absence;
+mod audit;
+mod auth;
+mod backup;
+mod badge;
+mod badge_sheet;
+mod clock;
+mod code128;
+mod config;
+mod error;
+mod i18n;
+mod migrations;
+mod pin;
+mod presence;
+mod recovery;
+mod recycle;
+mod schedule;
+mod session;
+mod storage;
+mod timesheet;
+mod timezone;
+mod users;
+mod workday;
+
+use backup::{BackupInfo, BackupKind, BackupSettings};
+use clock::ClockWatch;
+use chrono::{DateTime, NaiveDate, Utc};
+use error::AppError;
+use i18n::{tr, Locale};
+use presence::{PresenceState, PunchError, PunchOutcome, PunchType, RecordSource, SequencePolicy};
+use rusqlite::{params, Connection, OptionalExtension};
+use schedule::{ScheduleBook, ScheduleDay, WeeklySchedule};
 use rust_xlsxwriter::{Format, Workbook};
+use workday::WorkdayBoundary;
 use serde::{Deserialize, Serialize};
+use session::SessionStore;
+use recovery::{Recovery, RecoveryStatus, Startup};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+use users::{Permission, SessionUser, UserAccount};
+
+/// How often the daily backup is checked for.
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 struct AppState {
     db: Mutex<Connection>,
+    db_path: PathBuf,
+    /// Secret of an encrypted database.
+    key: Mutex<Option<String>>,
+    sessions: Mutex<SessionStore>,
+    clock: Mutex<ClockWatch>,
+    /// Why the last startup or daily backup failed, until one succeeds.
+    backup_error: Mutex<Option<String>>,
+}
+
+impl AppState {
+    fn current_user(&self, token: &str) -> Result<SessionUser, AppError> {
+        self.sessions.lock().unwrap().validate(token)
+    }
+
+    /// Validates the session and checks that its role grants `permission`.
+    fn authorize(&self, token: &str, permission: Permission) -> Result<SessionUser, AppError> {
+        let user = self.current_user(token)?;
+        if !user.role.allows(permission) {
+            return Err(AppError::auth(tr!("No tienes permiso para realizar esta acción")));
+        }
+        Ok(user)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -21,6 +83,32 @@ struct AttendanceRecord {
     notes: Option<String>,
     created_at: String,
     updated_at: String,
+    flag: Option<String>,
+    late_minutes: Option<i64>,
+    early_departure_minutes: Option<i64>,
+    deleted_at: Option<String>,
+    deleted_by: Option<String>,
+    source: String,
+    device_id: Option<String>,
+    /// When the punch happened; `timestamp` is its site wall-clock time.
+    #[serde(skip)]
+    instant: Option<DateTime<Utc>>,
+}
+
+const RECORD_COLUMNS: &str = "id, employee_id, employee_name, timestamp, type, notes, created_at, \
+                              updated_at, flag, deleted_at, deleted_by, source, device_id";
+
+/// Filters shared by the record listing, the export and the reports. Field
+/// names match the frontend's `RecordFilter`.
+#[derive(Deserialize, Default)]
+struct RecordFilters {
+    start_date: Option<String>,
+    end_date: Option<String>,
+    employee_id: Option<String>,
+    #[serde(rename = "type")]
+    record_type: Option<String>,
+    source: Option<String>,
+    device_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -30,166 +118,390 @@ struct Employee {
     active: bool,
     created_at: String,
     updated_at: String,
+    deleted_at: Option<String>,
+    deleted_by: Option<String>,
+    /// Whether a kiosk PIN is set. The hash itself never leaves the backend.
+    has_pin: bool,
 }
 
+const EMPLOYEE_COLUMNS: &str =
+    "id, name, active, created_at, updated_at, deleted_at, deleted_by, pin_hash IS NOT NULL";
+
 #[derive(Serialize)]
 struct DailyStats {
     total_entries: i64,
     total_exits: i64,
     unique_employees_present: i64,
     last_activity: Option<AttendanceRecord>,
+    absentees: Vec<absence::Absentee>,
 }
 
-fn init_database(conn: &Connection) {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS attendance (
-            id INTEGER PRIMARY KEY,
-            employee_id TEXT NOT NULL,
-            employee_name TEXT,
-            timestamp DATETIME DEFAULT (datetime('now')),
-            type TEXT CHECK(type IN ('entry', 'exit')),
-            notes TEXT,
-            created_at DATETIME DEFAULT (datetime('now')),
-            updated_at DATETIME DEFAULT (datetime('now'))
-        );
-
-        CREATE TABLE IF NOT EXISTS employees (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            active BOOLEAN DEFAULT 1,
-            created_at DATETIME DEFAULT (datetime('now')),
-            updated_at DATETIME DEFAULT (datetime('now'))
-        );
-
-        CREATE TABLE IF NOT EXISTS config (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        );
-
-        INSERT OR IGNORE INTO config (key, value) VALUES ('admin_password', '0824');
-        UPDATE config SET value = '0824' WHERE key = 'admin_password' AND value = '1234';
-
-        CREATE INDEX IF NOT EXISTS idx_employee_id ON attendance(employee_id);
-        CREATE INDEX IF NOT EXISTS idx_timestamp ON attendance(timestamp);
-        CREATE INDEX IF NOT EXISTS idx_type ON attendance(type);
-        CREATE INDEX IF NOT EXISTS idx_employee_active ON employees(active);",
-    )
-    .expect("Failed to initialize database");
-
-    // Explicitly drop the problematic index if it exists, as it causes errors on some platforms
-    let _ = conn.execute("DROP INDEX IF EXISTS idx_date", []);
+/// Opens, checks and migrates the database, returning what went wrong
+/// otherwise.
+fn open_database(db_path: &Path, key: Option<&str>) -> Result<Connection, Vec<String>> {
+    let conn = storage::open(db_path, key).map_err(|e| vec![e.to_string()])?;
+    let problems = recovery::check(&conn);
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+    init_database(&conn).map_err(|e| vec![e.to_string()])?;
+    Ok(conn)
+}
 
-    // Migrate: add columns that may be missing from older databases
-    for col in &[
-        ("notes", "TEXT"),
-        ("created_at", "DATETIME DEFAULT (datetime('now'))"),
-        ("updated_at", "DATETIME DEFAULT (datetime('now'))"),
-    ] {
-        let sql = format!("ALTER TABLE attendance ADD COLUMN {} {}", col.0, col.1);
-        // Ignore error — means column already exists
-        let _ = conn.execute(&sql, []);
+/// Opens the database and starts the app on it, or reports why it could not.
+fn start(app: &tauri::AppHandle, db_path: &Path, key: Option<String>) -> Startup {
+    match open_database(db_path, key.as_deref()) {
+        Ok(conn) => {
+            let backup_error = backup::create(&conn, BackupKind::Startup).err();
+            app.manage(AppState {
+                db: Mutex::new(conn),
+                db_path: db_path.to_path_buf(),
+                key: Mutex::new(key),
+                sessions: Mutex::new(SessionStore::default()),
+                clock: Mutex::new(ClockWatch::default()),
+                backup_error: Mutex::new(backup_error.map(|e| e.to_string())),
+            });
+            spawn_daily_backups(app.clone());
+            Startup::Ready
+        }
+        Err(problems) => Startup::Failed(problems),
     }
 }
 
+fn spawn_daily_backups(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(BACKUP_CHECK_INTERVAL);
+        let state = app.state::<AppState>();
+        let db = state.db.lock().unwrap();
+        let result = backup::ensure_daily(&db);
+        *state.backup_error.lock().unwrap() = result.err().map(|e| e.to_string());
+    });
+}
+
+fn init_database(conn: &Connection) -> Result<(), AppError> {
+    migrations::run(conn)?;
+    i18n::load(conn)?;
+    timezone::load(conn)?;
+    auth::migrate_legacy_password(conn)
+}
+
+/// Reads a stored UTC timestamp column as site wall-clock time.
+fn local_column(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<String> {
+    Ok(timezone::display(&row.get::<_, String>(idx)?))
+}
+
+fn local_column_opt(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<Option<String>> {
+    Ok(row.get::<_, Option<String>>(idx)?.as_deref().map(timezone::display))
+}
+
 fn row_to_record(row: &rusqlite::Row) -> rusqlite::Result<AttendanceRecord> {
     Ok(AttendanceRecord {
         id: row.get(0)?,
         employee_id: row.get(1)?,
         employee_name: row.get(2)?,
-        timestamp: row.get(3)?,
+        timestamp: local_column(row, 3)?,
         r#type: row.get(4)?,
         notes: row.get(5)?,
-        created_at: row.get(6)?,
-        updated_at: row.get(7)?,
+        created_at: local_column(row, 6)?,
+        updated_at: local_column(row, 7)?,
+        flag: row.get(8)?,
+        late_minutes: None,
+        early_departure_minutes: None,
+        deleted_at: local_column_opt(row, 9)?,
+        deleted_by: row.get(10)?,
+        source: row.get(11)?,
+        device_id: row.get(12)?,
+        instant: timezone::parse_utc(&row.get::<_, String>(3)?),
+    })
+}
+
+fn fetch_record(db: &Connection, id: i64) -> rusqlite::Result<Option<AttendanceRecord>> {
+    let sql = format!("SELECT {} FROM attendance WHERE id = ?1", RECORD_COLUMNS);
+    db.query_row(&sql, params![id], row_to_record).optional()
+}
+
+fn row_to_employee(row: &rusqlite::Row) -> rusqlite::Result<Employee> {
+    Ok(Employee {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        active: row.get(2)?,
+        created_at: local_column(row, 3)?,
+        updated_at: local_column(row, 4)?,
+        deleted_at: local_column_opt(row, 5)?,
+        deleted_by: row.get(6)?,
+        has_pin: row.get(7)?,
     })
 }
 
+fn fetch_employee(db: &Connection, id: &str) -> rusqlite::Result<Option<Employee>> {
+    let sql = format!("SELECT {} FROM employees WHERE id = ?1", EMPLOYEE_COLUMNS);
+    db.query_row(&sql, params![id], row_to_employee).optional()
+}
+
+/// Name of an employee allowed to punch by ID: one that is active and not
+/// deleted, as `badge::resolve` requires for badge punches.
+fn punching_employee_name(db: &Connection, employee_id: &str) -> Result<String, AppError> {
+    db.query_row(
+        "SELECT name FROM employees WHERE id = ?1 AND active = 1 AND deleted_at IS NULL",
+        params![employee_id],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found(tr!("Empleado no encontrado o inactivo")))
+}
+
 #[tauri::command]
 fn check_in(
     state: tauri::State<AppState>,
     employee_id: String,
-    employee_name: Option<String>,
-) -> Result<String, String> {
+    pin: Option<String>,
+) -> Result<PunchOutcome, PunchError> {
     let db = state.db.lock().unwrap();
+    let name = punching_employee_name(&db, &employee_id)?;
+    pin::verify(&db, &employee_id, pin.as_deref())?;
+    let clock_jump = state.clock.lock().unwrap().check(&db)?;
+    presence::record_punch(&db, &employee_id, &name, PunchType::Entry, clock_jump.as_ref())
+}
 
-    // Try to get employee name from database if not provided
-    let name = if let Some(n) = employee_name {
-        n
-    } else {
-        db.query_row(
-            "SELECT name FROM employees WHERE id = ?1",
-            params![&employee_id],
-            |row| row.get::<_, String>(0),
-        )
-        .unwrap_or_else(|_| format!("Empleado {}", employee_id))
-    };
+#[tauri::command]
+fn check_out(
+    state: tauri::State<AppState>,
+    employee_id: String,
+    pin: Option<String>,
+) -> Result<PunchOutcome, PunchError> {
+    let db = state.db.lock().unwrap();
+    let name = punching_employee_name(&db, &employee_id)?;
+    pin::verify(&db, &employee_id, pin.as_deref())?;
+    let clock_jump = state.clock.lock().unwrap().check(&db)?;
+    presence::record_punch(&db, &employee_id, &name, PunchType::Exit, clock_jump.as_ref())
+}
 
-    db.execute(
-        "INSERT INTO attendance (employee_id, employee_name, type, timestamp, created_at, updated_at) 
-         VALUES (?1, ?2, 'entry', datetime('now', 'localtime'), datetime('now', 'localtime'), datetime('now', 'localtime'))",
-        params![employee_id, name],
-    )
-    .map_err(|e| e.to_string())?;
+#[derive(Serialize)]
+struct BadgePunch {
+    employee_id: String,
+    employee_name: String,
+    #[serde(flatten)]
+    outcome: PunchOutcome,
+}
 
-    Ok("Entrada registrada".to_string())
+/// Punches whoever the scanned badge belongs to: an entry when they are out,
+/// an exit when they are in.
+#[tauri::command]
+fn punch_by_badge(
+    state: tauri::State<AppState>,
+    code: String,
+    pin: Option<String>,
+) -> Result<BadgePunch, PunchError> {
+    let db = state.db.lock().unwrap();
+    let Some((employee_id, employee_name)) = badge::resolve(&db, &code)? else {
+        return Err(AppError::not_found(tr!("Gafete no reconocido")).into());
+    };
+    pin::verify(&db, &employee_id, pin.as_deref())?;
+    let punch = match presence::current_state(&db, &employee_id)? {
+        PresenceState::Present => PunchType::Exit,
+        PresenceState::Absent => PunchType::Entry,
+    };
+    let clock_jump = state.clock.lock().unwrap().check(&db)?;
+    let outcome = presence::record_punch(&db, &employee_id, &employee_name, punch, clock_jump.as_ref())?;
+    Ok(BadgePunch {
+        employee_id,
+        employee_name,
+        outcome,
+    })
 }
 
 #[tauri::command]
-fn check_out(
+fn get_presence_state(
     state: tauri::State<AppState>,
     employee_id: String,
-    employee_name: Option<String>,
-) -> Result<String, String> {
+) -> Result<PresenceState, AppError> {
     let db = state.db.lock().unwrap();
+    presence::current_state(&db, &employee_id).map_err(AppError::from)
+}
 
-    // Try to get employee name from database if not provided
-    let name = if let Some(n) = employee_name {
-        n
-    } else {
-        db.query_row(
-            "SELECT name FROM employees WHERE id = ?1",
-            params![&employee_id],
-            |row| row.get::<_, String>(0),
-        )
-        .unwrap_or_else(|_| format!("Empleado {}", employee_id))
-    };
+/// Read by the kiosk, which has no session, to know whether to ask for a PIN.
+#[tauri::command]
+fn get_pin_required(state: tauri::State<AppState>) -> Result<bool, AppError> {
+    let db = state.db.lock().unwrap();
+    pin::is_required(&db).map_err(AppError::from)
+}
 
-    db.execute(
-        "INSERT INTO attendance (employee_id, employee_name, type, timestamp, created_at, updated_at) 
-         VALUES (?1, ?2, 'exit', datetime('now', 'localtime'), datetime('now', 'localtime'), datetime('now', 'localtime'))",
-        params![employee_id, name],
-    )
-    .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn set_pin_required(state: tauri::State<AppState>, token: String, required: bool) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    pin::set_required(&db, required)?;
+    Ok(tr!("Configuración actualizada"))
+}
+
+#[tauri::command]
+fn get_sequence_policy(
+    state: tauri::State<AppState>,
+    token: String,
+) -> Result<SequencePolicy, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    SequencePolicy::load(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn set_sequence_policy(
+    state: tauri::State<AppState>,
+    token: String,
+    policy: SequencePolicy,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    policy.save(&db)?;
+    Ok(tr!("Configuración actualizada"))
+}
+
+#[tauri::command]
+fn get_workday_start(state: tauri::State<AppState>, token: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    let boundary = WorkdayBoundary::load(&db)?;
+    Ok(boundary.start_time())
+}
+
+#[tauri::command]
+fn set_workday_start(
+    state: tauri::State<AppState>,
+    token: String,
+    start_time: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    WorkdayBoundary::save(&db, &start_time)?;
+    Ok(tr!("Configuración actualizada"))
+}
+
+#[tauri::command]
+fn get_locale(state: tauri::State<AppState>, token: String) -> Result<Locale, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    Ok(i18n::current())
+}
+
+#[tauri::command]
+fn set_locale(state: tauri::State<AppState>, token: String, locale: Locale) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    i18n::save(&db, locale)?;
+    Ok(tr!("Configuración actualizada"))
+}
+
+#[tauri::command]
+fn get_timezone(state: tauri::State<AppState>, token: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    Ok(timezone::current().name().to_string())
+}
+
+#[tauri::command]
+fn get_timezones() -> Vec<&'static str> {
+    timezone::names()
+}
+
+#[tauri::command]
+fn set_timezone(state: tauri::State<AppState>, token: String, timezone: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    timezone::save(&db, &timezone)?;
+    Ok(tr!("Configuración actualizada"))
+}
 
-    Ok("Salida registrada".to_string())
+#[tauri::command]
+fn get_device_id(state: tauri::State<AppState>, token: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    presence::device_id(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_database_path(state: tauri::State<AppState>, token: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    Ok(state.db_path.display().to_string())
+}
+
+#[tauri::command]
+fn get_encryption_status(state: tauri::State<AppState>, token: String) -> Result<bool, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    Ok(storage::is_encrypted(&state.db_path))
+}
+
+#[tauri::command]
+fn encrypt_database(state: tauri::State<AppState>, token: String, secret: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let mut db = state.db.lock().unwrap();
+    storage::encrypt(&mut db, &secret)?;
+    *state.key.lock().unwrap() = Some(secret.clone());
+    let converted = backup::encrypt_all(&db, &secret)?;
+    Ok(tr!(
+        "Base de datos cifrada ({} respaldos cifrados). La clave se pedirá al iniciar la aplicación.",
+        converted
+    ))
+}
+
+#[tauri::command]
+fn set_device_id(
+    state: tauri::State<AppState>,
+    token: String,
+    device_id: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    presence::set_device_id(&db, &device_id)?;
+    Ok(tr!("Configuración actualizada"))
 }
 
 #[tauri::command]
 fn get_records(
     state: tauri::State<AppState>,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    employee_id: Option<String>,
-    record_type: Option<String>,
-) -> Result<Vec<AttendanceRecord>, String> {
+    token: String,
+    filters: RecordFilters,
+) -> Result<Vec<AttendanceRecord>, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    validate_filters(&filters)?;
     let db = state.db.lock().unwrap();
+    let mut records = query_records(&db, &filters)?;
+
+    let deviations =
+        schedule_deviations(&db, &filters.start_date, &filters.end_date, &filters.employee_id)?;
+    for deviation in deviations {
+        if let Some(record) = records.iter_mut().find(|r| r.id == deviation.record_id) {
+            match deviation.kind {
+                schedule::DeviationKind::Late => record.late_minutes = Some(deviation.minutes),
+                schedule::DeviationKind::EarlyDeparture => {
+                    record.early_departure_minutes = Some(deviation.minutes)
+                }
+            }
+        }
+    }
 
-    let mut sql = String::from(
-        "SELECT id, employee_id, employee_name, timestamp, type, notes, created_at, updated_at FROM attendance WHERE 1=1",
-    );
+    Ok(records)
+}
+
+fn validate_filters(filters: &RecordFilters) -> Result<(), AppError> {
+    if let Some(ref source) = filters.source {
+        RecordSource::parse(source)
+            .ok_or_else(|| AppError::validation(tr!("Origen inválido: {}", source)))?;
+    }
+    Ok(())
+}
+
+fn query_records(db: &Connection, filters: &RecordFilters) -> rusqlite::Result<Vec<AttendanceRecord>> {
+    let boundary = WorkdayBoundary::load(db)?;
+    let mut sql = format!("SELECT {} FROM attendance WHERE deleted_at IS NULL", RECORD_COLUMNS);
     let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
 
-    build_query_filters(&mut sql, &mut param_values, &start_date, &end_date, &employee_id, &record_type);
+    build_query_filters(&mut sql, &mut param_values, &boundary, filters);
     sql.push_str(" ORDER BY timestamp DESC");
 
     let params_refs: Vec<&dyn rusqlite::types::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
 
-    let mut stmt = db.prepare(&sql).map_err(|e| e.to_string())?;
+    let mut stmt = db.prepare(&sql)?;
     let records = stmt
-        .query_map(params_refs.as_slice(), row_to_record)
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        .query_map(params_refs.as_slice(), row_to_record)?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(records)
 }
@@ -197,99 +509,371 @@ fn get_records(
 fn build_query_filters(
     sql: &mut String,
     param_values: &mut Vec<Box<dyn rusqlite::types::ToSql>>,
-    start_date: &Option<String>,
-    end_date: &Option<String>,
-    employee_id: &Option<String>,
-    record_type: &Option<String>,
+    boundary: &WorkdayBoundary,
+    filters: &RecordFilters,
 ) {
-    if let Some(ref sd) = start_date {
-        param_values.push(Box::new(sd.clone()));
-        sql.push_str(&format!(" AND date(timestamp, 'localtime') >= date(?{})", param_values.len()));
+    // Dates are workdays in site time and become a half-open UTC range. An
+    // unparseable date matches nothing.
+    let parse = |d: &String| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+    if let Some(ref sd) = filters.start_date {
+        match parse(sd) {
+            Some(day) => {
+                param_values.push(Box::new(boundary.day_start(day)));
+                sql.push_str(&format!(" AND timestamp >= ?{}", param_values.len()));
+            }
+            None => sql.push_str(" AND 0"),
+        }
     }
-    if let Some(ref ed) = end_date {
-        param_values.push(Box::new(ed.clone()));
-        sql.push_str(&format!(" AND date(timestamp, 'localtime') <= date(?{})", param_values.len()));
+    if let Some(ref ed) = filters.end_date {
+        match parse(ed) {
+            Some(day) => {
+                param_values.push(Box::new(boundary.day_start(day + chrono::Duration::days(1))));
+                sql.push_str(&format!(" AND timestamp < ?{}", param_values.len()));
+            }
+            None => sql.push_str(" AND 0"),
+        }
     }
-    if let Some(ref eid) = employee_id {
+    if let Some(ref eid) = filters.employee_id {
         param_values.push(Box::new(eid.clone()));
         sql.push_str(&format!(" AND employee_id = ?{}", param_values.len()));
     }
-    if let Some(ref rt) = record_type {
+    if let Some(ref rt) = filters.record_type {
         param_values.push(Box::new(rt.clone()));
         sql.push_str(&format!(" AND type = ?{}", param_values.len()));
     }
+    if let Some(ref source) = filters.source {
+        param_values.push(Box::new(source.clone()));
+        sql.push_str(&format!(" AND source = ?{}", param_values.len()));
+    }
+    if let Some(ref device) = filters.device_id {
+        param_values.push(Box::new(device.clone()));
+        sql.push_str(&format!(" AND device_id = ?{}", param_values.len()));
+    }
+}
+
+#[tauri::command]
+fn get_timesheet(
+    state: tauri::State<AppState>,
+    token: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    employee_id: Option<String>,
+) -> Result<Vec<timesheet::EmployeeTimesheet>, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+
+    // Fetch one extra day on each side so shifts crossing the range edges still pair up
+    let filters = RecordFilters {
+        start_date: timesheet::pad_date(&start_date, -1),
+        end_date: timesheet::pad_date(&end_date, 1),
+        employee_id,
+        ..Default::default()
+    };
+    let records = query_records(&db, &filters)?;
+
+    let boundary = WorkdayBoundary::load(&db)?;
+    Ok(timesheet::build(&records, &boundary, &start_date, &end_date))
+}
+
+/// Schedule deviations for every workday touched by the date range. Records are
+/// fetched regardless of type so first entries and last exits can be identified.
+fn schedule_deviations(
+    db: &Connection,
+    start_date: &Option<String>,
+    end_date: &Option<String>,
+    employee_id: &Option<String>,
+) -> rusqlite::Result<Vec<schedule::Deviation>> {
+    let filters = RecordFilters {
+        start_date: timesheet::pad_date(start_date, -1),
+        end_date: timesheet::pad_date(end_date, 1),
+        employee_id: employee_id.clone(),
+        ..Default::default()
+    };
+    let records = query_records(db, &filters)?;
+    let book = ScheduleBook::load(db)?;
+    let boundary = WorkdayBoundary::load(db)?;
+    let grace = schedule::grace_minutes(db)?;
+    Ok(schedule::deviations(&records, &book, &boundary, grace))
+}
+
+#[tauri::command]
+fn get_tardiness_report(
+    state: tauri::State<AppState>,
+    token: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    employee_id: Option<String>,
+) -> Result<Vec<schedule::TardinessSummary>, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    let deviations = schedule_deviations(&db, &start_date, &end_date, &employee_id)?
+        .into_iter()
+        .filter(|d| {
+            start_date.as_ref().is_none_or(|s| d.workday >= *s)
+                && end_date.as_ref().is_none_or(|e| d.workday <= *e)
+        })
+        .collect();
+    Ok(schedule::summarize(deviations))
 }
 
 #[tauri::command]
-fn get_daily_stats(state: tauri::State<AppState>) -> Result<DailyStats, String> {
+fn get_absences(
+    state: tauri::State<AppState>,
+    token: String,
+    start_date: String,
+    end_date: String,
+    employee_id: Option<String>,
+) -> Result<Vec<absence::EmployeeAbsences>, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
     let db = state.db.lock().unwrap();
+    absence::absences(&db, &start_date, &end_date, &employee_id)
+}
+
+#[tauri::command]
+fn get_holidays(
+    state: tauri::State<AppState>,
+    token: String,
+) -> Result<Vec<absence::Holiday>, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    absence::get_holidays(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn add_holiday(
+    state: tauri::State<AppState>,
+    token: String,
+    date: String,
+    name: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    absence::add_holiday(&db, &date, &name)?;
+    Ok(tr!("Día festivo guardado"))
+}
+
+#[tauri::command]
+fn delete_holiday(
+    state: tauri::State<AppState>,
+    token: String,
+    date: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    absence::delete_holiday(&db, &date)?;
+    Ok(tr!("Día festivo eliminado"))
+}
+
+#[tauri::command]
+fn get_employee_schedules(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_id: String,
+) -> Result<Vec<WeeklySchedule>, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    schedule::get_schedules(&db, &employee_id).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn set_employee_schedule(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_id: String,
+    effective_from: String,
+    days: Vec<ScheduleDay>,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+    schedule::set_schedule(&db, &employee_id, &effective_from, &days)?;
+    Ok(tr!("Horario guardado"))
+}
+
+#[tauri::command]
+fn delete_employee_schedule(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_id: String,
+    effective_from: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+    schedule::delete_schedule(&db, &employee_id, &effective_from)?;
+    Ok(tr!("Horario eliminado"))
+}
+
+#[tauri::command]
+fn get_grace_minutes(state: tauri::State<AppState>, token: String) -> Result<i64, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+    schedule::grace_minutes(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn set_grace_minutes(
+    state: tauri::State<AppState>,
+    token: String,
+    minutes: i64,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    schedule::set_grace_minutes(&db, minutes)?;
+    Ok(tr!("Configuración actualizada"))
+}
+
+#[tauri::command]
+fn get_daily_stats(state: tauri::State<AppState>, token: String) -> Result<DailyStats, AppError> {
+    state.authorize(&token, Permission::ViewRecords)?;
+    let db = state.db.lock().unwrap();
+
+    let boundary = WorkdayBoundary::load(&db)?;
+    // Live rows of the current workday
+    let day = boundary.today();
+    let (start, end) = (boundary.day_start(day), boundary.day_start(day + chrono::Duration::days(1)));
+    let today = "deleted_at IS NULL AND timestamp >= ?1 AND timestamp < ?2";
 
     let total_entries: i64 = db
         .query_row(
-            "SELECT COUNT(*) FROM attendance WHERE type = 'entry' AND date(timestamp, 'localtime') = date('now', 'localtime')",
-            [],
+            &format!("SELECT COUNT(*) FROM attendance WHERE type = 'entry' AND {}", today),
+            params![start, end],
             |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let total_exits: i64 = db
         .query_row(
-            "SELECT COUNT(*) FROM attendance WHERE type = 'exit' AND date(timestamp, 'localtime') = date('now', 'localtime')",
-            [],
+            &format!("SELECT COUNT(*) FROM attendance WHERE type = 'exit' AND {}", today),
+            params![start, end],
             |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let unique_employees_present: i64 = db
         .query_row(
-            "SELECT COUNT(DISTINCT employee_id) FROM attendance
-             WHERE type = 'entry' AND date(timestamp, 'localtime') = date('now', 'localtime')
-             AND employee_id NOT IN (
-                 SELECT employee_id FROM attendance
-                 WHERE type = 'exit' AND date(timestamp, 'localtime') = date('now', 'localtime')
-             )",
-            [],
+            &format!(
+                "SELECT COUNT(DISTINCT employee_id) FROM attendance
+                 WHERE type = 'entry' AND {today}
+                 AND employee_id NOT IN (
+                     SELECT employee_id FROM attendance
+                     WHERE type = 'exit' AND {today}
+                 )"
+            ),
+            params![start, end],
             |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
     let last_activity: Option<AttendanceRecord> = db
         .query_row(
-            "SELECT id, employee_id, employee_name, timestamp, type, notes, created_at, updated_at
-             FROM attendance WHERE date(timestamp, 'localtime') = date('now', 'localtime')
-             ORDER BY timestamp DESC LIMIT 1",
-            [],
+            &format!(
+                "SELECT {} FROM attendance WHERE {} ORDER BY timestamp DESC LIMIT 1",
+                RECORD_COLUMNS, today
+            ),
+            params![start, end],
             row_to_record,
         )
         .ok();
 
+    let absentees = absence::todays_absentees(&db)?;
+
     Ok(DailyStats {
         total_entries,
         total_exits,
         unique_employees_present,
         last_activity,
+        absentees,
     })
 }
 
+/// Adds a punch an employee missed (forgotten badge, kiosk down) at an
+/// arbitrary past time. Recorded with `source = manual` and audited.
+#[tauri::command]
+fn create_manual_record(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_id: String,
+    record_type: String,
+    timestamp: String,
+    notes: Option<String>,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::EditRecords)?;
+
+    let punch = PunchType::parse(&record_type)
+        .ok_or_else(|| AppError::validation(tr!("Tipo de registro inválido")))?;
+    let instant = timezone::parse_input(&timestamp)?;
+    if instant > Utc::now() {
+        return Err(AppError::validation(tr!("No se pueden registrar horas futuras")));
+    }
+    let timestamp = timezone::format_utc(instant);
+    let notes = notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    let db = state.db.lock().unwrap();
+    let tx = db.unchecked_transaction()?;
+
+    let Some(employee) = fetch_employee(&tx, employee_id.trim())?
+        .filter(|e| e.deleted_at.is_none())
+    else {
+        return Err(AppError::not_found(tr!("Empleado no encontrado")));
+    };
+
+    let id = presence::record_manual_punch(
+        &tx,
+        &employee.id,
+        &employee.name,
+        punch,
+        &timestamp,
+        notes.as_deref(),
+    )
+    .map_err(|e| e.error)?;
+    let after = fetch_record(&tx, id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "create_manual_record",
+        audit::Change {
+            entity: audit::Entity::Attendance,
+            entity_id: id.to_string(),
+            employee_id: employee.id.clone(),
+            before: None,
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Registro manual agregado"))
+}
+
 #[tauri::command]
 fn update_record(
     state: tauri::State<AppState>,
+    token: String,
     id: i64,
     timestamp: Option<String>,
     record_type: Option<String>,
     notes: Option<String>,
-) -> Result<String, String> {
+    reason: Option<String>,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::EditRecords)?;
     let db = state.db.lock().unwrap();
 
+    let new_instant = timestamp.as_deref().map(timezone::parse_input).transpose()?;
+    if new_instant.is_some_and(|instant| instant > Utc::now()) {
+        return Err(AppError::validation(tr!("No se pueden registrar horas futuras")));
+    }
+    let new_timestamp = new_instant.map(timezone::format_utc);
+    let new_type = record_type
+        .as_deref()
+        .map(|rt| PunchType::parse(rt).ok_or_else(|| AppError::validation(tr!("Tipo de registro inválido"))))
+        .transpose()?;
+
     let mut sets: Vec<String> = Vec::new();
     let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
 
-    if let Some(ref ts) = timestamp {
+    if let Some(ref ts) = new_timestamp {
         param_values.push(Box::new(ts.clone()));
         sets.push(format!("timestamp = ?{}", param_values.len()));
     }
-    if let Some(ref rt) = record_type {
-        param_values.push(Box::new(rt.clone()));
+    if let Some(punch) = new_type {
+        param_values.push(Box::new(punch.as_str()));
         sets.push(format!("type = ?{}", param_values.len()));
     }
     if let Some(ref n) = notes {
@@ -298,10 +882,10 @@ fn update_record(
     }
 
     if sets.is_empty() {
-        return Err("No fields to update".to_string());
+        return Err(AppError::validation(tr!("No hay campos para actualizar")));
     }
 
-    sets.push("updated_at = datetime('now', 'localtime')".to_string());
+    sets.push("updated_at = datetime('now')".to_string());
     param_values.push(Box::new(id));
     let sql = format!(
         "UPDATE attendance SET {} WHERE id = ?{}",
@@ -312,30 +896,104 @@ fn update_record(
     let params_refs: Vec<&dyn rusqlite::types::ToSql> =
         param_values.iter().map(|p| p.as_ref()).collect();
 
-    let rows_affected = db
-        .execute(&sql, params_refs.as_slice())
-        .map_err(|e| e.to_string())?;
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_record(&tx, id)?
+        .filter(|r| r.deleted_at.is_none())
+    else {
+        return Err(AppError::not_found(tr!("Registro no encontrado")));
+    };
+
+    // Changing when or what was punched is a correction and must be justified;
+    // notes alone can be edited freely
+    let timestamp_changed = timestamp.as_deref().is_some_and(|ts| {
+        timesheet::parse_timestamp(ts) != timesheet::parse_timestamp(&before.timestamp)
+    });
+    let type_changed = new_type.is_some_and(|t| t.as_str() != before.r#type);
+    let reason = if timestamp_changed || type_changed {
+        Some(audit::require_reason(&reason)?)
+    } else {
+        reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty())
+    };
 
-    if rows_affected == 0 {
-        return Err("Record not found".to_string());
+    // A correction must leave the sequence as consistent as a manual punch
+    if timestamp_changed || type_changed {
+        let punch = match new_type {
+            Some(punch) => punch,
+            None => PunchType::parse(&before.r#type)
+                .ok_or_else(|| AppError::validation(tr!("Tipo de registro inválido")))?,
+        };
+        let ts = match new_timestamp {
+            Some(ts) => ts,
+            None => tx.query_row(
+                "SELECT timestamp FROM attendance WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )?,
+        };
+        presence::check_edit(&tx, id, &before.employee_id, punch, &ts).map_err(|e| e.error)?;
     }
 
-    Ok("Registro actualizado".to_string())
+    tx.execute(&sql, params_refs.as_slice())?;
+    let after = fetch_record(&tx, id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "update_record",
+        audit::Change {
+            entity: audit::Entity::Attendance,
+            entity_id: id.to_string(),
+            employee_id: before.employee_id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Registro actualizado"))
 }
 
 #[tauri::command]
-fn delete_record(state: tauri::State<AppState>, id: i64) -> Result<String, String> {
+fn delete_record(
+    state: tauri::State<AppState>,
+    token: String,
+    id: i64,
+    reason: Option<String>,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::DeleteRecords)?;
+    let reason = audit::require_reason(&reason)?;
     let db = state.db.lock().unwrap();
 
-    let rows_affected = db
-        .execute("DELETE FROM attendance WHERE id = ?1", params![id])
-        .map_err(|e| e.to_string())?;
-
-    if rows_affected == 0 {
-        return Err("Record not found".to_string());
-    }
-
-    Ok("Registro eliminado".to_string())
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_record(&tx, id)?
+        .filter(|r| r.deleted_at.is_none())
+    else {
+        return Err(AppError::not_found(tr!("Registro no encontrado")));
+    };
+    tx.execute(
+        "UPDATE attendance SET deleted_at = datetime('now'), deleted_by = ?1
+         WHERE id = ?2",
+        params![user.username, id],
+    )?;
+    let after = fetch_record(&tx, id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "delete_record",
+        audit::Change {
+            entity: audit::Entity::Attendance,
+            entity_id: id.to_string(),
+            employee_id: before.employee_id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: Some(reason),
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Registro eliminado"))
 }
 
 // Employee management commands
@@ -343,29 +1001,22 @@ fn delete_record(state: tauri::State<AppState>, id: i64) -> Result<String, Strin
 fn get_employees(
     state: tauri::State<AppState>,
     active_only: Option<bool>,
-) -> Result<Vec<Employee>, String> {
+) -> Result<Vec<Employee>, AppError> {
     let db = state.db.lock().unwrap();
 
     let sql = if active_only.unwrap_or(false) {
-        "SELECT id, name, active, created_at, updated_at FROM employees WHERE active = 1 ORDER BY name"
+        format!(
+            "SELECT {} FROM employees WHERE deleted_at IS NULL AND active = 1 ORDER BY name",
+            EMPLOYEE_COLUMNS
+        )
     } else {
-        "SELECT id, name, active, created_at, updated_at FROM employees ORDER BY name"
+        format!("SELECT {} FROM employees WHERE deleted_at IS NULL ORDER BY name", EMPLOYEE_COLUMNS)
     };
 
-    let mut stmt = db.prepare(sql).map_err(|e| e.to_string())?;
+    let mut stmt = db.prepare(&sql)?;
     let employees = stmt
-        .query_map([], |row| {
-            Ok(Employee {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                active: row.get(2)?,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        .query_map([], row_to_employee)?
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(employees)
 }
@@ -373,41 +1024,68 @@ fn get_employees(
 #[tauri::command]
 fn create_employee(
     state: tauri::State<AppState>,
+    token: String,
     id: String,
     name: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
     let db = state.db.lock().unwrap();
 
     // Validate inputs
     if id.trim().is_empty() {
-        return Err("El ID del empleado no puede estar vacío".to_string());
+        return Err(AppError::validation(tr!("El ID del empleado no puede estar vacío")));
     }
     if name.trim().is_empty() {
-        return Err("El nombre del empleado no puede estar vacío".to_string());
+        return Err(AppError::validation(tr!("El nombre del empleado no puede estar vacío")));
     }
 
-    db.execute(
+    let tx = db.unchecked_transaction()?;
+    tx.execute(
         "INSERT INTO employees (id, name) VALUES (?1, ?2)",
         params![id.trim().to_string(), name.trim().to_string()],
     )
     .map_err(|e| {
-        if e.to_string().contains("UNIQUE constraint failed") {
-            "Ya existe un empleado con ese ID".to_string()
+        if !error::is_unique_violation(&e) {
+            AppError::from(e)
+        } else if fetch_employee(&tx, id.trim())
+            .ok()
+            .flatten()
+            .is_some_and(|e| e.deleted_at.is_some())
+        {
+            AppError::conflict(tr!("Ya existe un empleado con ese ID en la papelera; restáuralo en lugar de crearlo"))
         } else {
-            e.to_string()
+            AppError::conflict(tr!("Ya existe un empleado con ese ID"))
         }
     })?;
-
-    Ok("Empleado creado exitosamente".to_string())
+    let after = fetch_employee(&tx, id.trim())?;
+
+    audit::record(
+        &tx,
+        &user,
+        "create_employee",
+        audit::Change {
+            entity: audit::Entity::Employee,
+            entity_id: id.trim().to_string(),
+            employee_id: id.trim().to_string(),
+            before: None,
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Empleado creado exitosamente"))
 }
 
 #[tauri::command]
 fn update_employee(
     state: tauri::State<AppState>,
+    token: String,
     id: String,
     name: Option<String>,
     active: Option<bool>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
     let db = state.db.lock().unwrap();
 
     let mut sets: Vec<String> = Vec::new();
@@ -415,7 +1093,7 @@ fn update_employee(
 
     if let Some(ref n) = name {
         if n.trim().is_empty() {
-            return Err("El nombre del empleado no puede estar vacío".to_string());
+            return Err(AppError::validation(tr!("El nombre del empleado no puede estar vacío")));
         }
         param_values.push(Box::new(n.trim().to_string()));
         sets.push(format!("name = ?{}", param_values.len()));
@@ -426,10 +1104,10 @@ fn update_employee(
     }
 
     if sets.is_empty() {
-        return Err("No hay campos para actualizar".to_string());
+        return Err(AppError::validation(tr!("No hay campos para actualizar")));
     }
 
-    sets.push("updated_at = datetime('now', 'localtime')".to_string());
+    sets.push("updated_at = datetime('now')".to_string());
     param_values.push(Box::new(id.clone()));
     let sql = format!(
         "UPDATE employees SET {} WHERE id = ?{}",
@@ -440,80 +1118,552 @@ fn update_employee(
     let params_refs: Vec<&dyn rusqlite::types::ToSql> =
         param_values.iter().map(|p| p.as_ref()).collect();
 
-    let rows_affected = db
-        .execute(&sql, params_refs.as_slice())
-        .map_err(|e| e.to_string())?;
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_employee(&tx, &id)?
+        .filter(|e| e.deleted_at.is_none())
+    else {
+        return Err(AppError::not_found(tr!("Empleado no encontrado")));
+    };
+    tx.execute(&sql, params_refs.as_slice())?;
+    let after = fetch_employee(&tx, &id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "update_employee",
+        audit::Change {
+            entity: audit::Entity::Employee,
+            entity_id: id.clone(),
+            employee_id: id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Empleado actualizado exitosamente"))
+}
+
+/// Sets a new kiosk PIN for the employee, or removes it when `pin` is empty,
+/// and lifts any lockout from wrong attempts.
+#[tauri::command]
+fn reset_employee_pin(
+    state: tauri::State<AppState>,
+    token: String,
+    id: String,
+    pin: Option<String>,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+    let pin = pin.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_employee(&tx, &id)?
+        .filter(|e| e.deleted_at.is_none())
+    else {
+        return Err(AppError::not_found(tr!("Empleado no encontrado")));
+    };
+    pin::reset(&tx, &id, pin.as_deref())?;
+    let after = fetch_employee(&tx, &id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "reset_employee_pin",
+        audit::Change {
+            entity: audit::Entity::Employee,
+            entity_id: id.clone(),
+            employee_id: id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(match pin {
+        Some(_) => tr!("PIN actualizado"),
+        None => tr!("PIN eliminado"),
+    })
+}
+
+#[tauri::command]
+fn get_badges(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_id: Option<String>,
+) -> Result<Vec<badge::Badge>, AppError> {
+    state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+    badge::list(&db, employee_id.as_deref()).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn assign_badge(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_id: String,
+    code: String,
+    label: Option<String>,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+
+    let tx = db.unchecked_transaction()?;
+    let badge = badge::assign(&tx, &employee_id, &code, label.as_deref())?;
+    audit::record(
+        &tx,
+        &user,
+        "assign_badge",
+        audit::Change {
+            entity: audit::Entity::Badge,
+            entity_id: badge.code.clone(),
+            employee_id: badge.employee_id.clone(),
+            before: None,
+            after: audit::snapshot(&badge),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Gafete asignado"))
+}
+
+#[tauri::command]
+fn revoke_badge(state: tauri::State<AppState>, token: String, code: String) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+
+    let tx = db.unchecked_transaction()?;
+    let badge = badge::revoke(&tx, &code)?;
+    audit::record(
+        &tx,
+        &user,
+        "revoke_badge",
+        audit::Change {
+            entity: audit::Entity::Badge,
+            entity_id: badge.code.clone(),
+            employee_id: badge.employee_id.clone(),
+            before: audit::snapshot(&badge),
+            after: None,
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Gafete revocado"))
+}
+
+/// Saves a PDF of badge cards for the given employees at `path`, or on the
+/// desktop when no path is given. Each card carries the employee's newest
+/// badge; employees without one are issued a new code.
+#[tauri::command]
+fn export_badge_sheet(
+    state: tauri::State<AppState>,
+    token: String,
+    employee_ids: Vec<String>,
+    path: Option<String>,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
 
-    if rows_affected == 0 {
-        return Err("Empleado no encontrado".to_string());
+    if employee_ids.is_empty() {
+        return Err(AppError::validation(tr!("Selecciona al menos un empleado")));
     }
+    let path = match path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) if !Path::new(p).is_absolute() => {
+            return Err(AppError::validation(tr!("La ruta del archivo debe ser una ruta completa")));
+        }
+        Some(p) => {
+            let is_pdf = Path::new(p)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+            if is_pdf {
+                PathBuf::from(p)
+            } else {
+                PathBuf::from(format!("{}.pdf", p))
+            }
+        }
+        None => {
+            let today = timezone::now_local().format("%Y-%m-%d").to_string();
+            export_path(&tr!("Gafetes_{}.pdf", today))
+        }
+    };
 
-    Ok("Empleado actualizado exitosamente".to_string())
+    // Issued badges are only kept if the sheet was written
+    let tx = db.unchecked_transaction()?;
+    let mut cards = Vec::new();
+    for id in &employee_ids {
+        let Some(employee) = fetch_employee(&tx, id)?.filter(|e| e.deleted_at.is_none()) else {
+            return Err(AppError::not_found(tr!("Empleado no encontrado")));
+        };
+        let badge = match badge::latest(&tx, id)? {
+            Some(badge) => badge,
+            None => {
+                let badge = badge::assign(&tx, id, &badge::generate_code(), Some(&tr!("Gafete impreso")))?;
+                audit::record(
+                    &tx,
+                    &user,
+                    "assign_badge",
+                    audit::Change {
+                        entity: audit::Entity::Badge,
+                        entity_id: badge.code.clone(),
+                        employee_id: id.clone(),
+                        before: None,
+                        after: audit::snapshot(&badge),
+                        reason: None,
+                    },
+                )?;
+                badge
+            }
+        };
+        cards.push(badge_sheet::Card {
+            employee_id: employee.id,
+            name: employee.name,
+            code: badge.code,
+        });
+    }
+    badge_sheet::render(&cards, &path)?;
+    tx.commit()?;
+
+    Ok(tr!("{} gafetes guardados en {}", cards.len(), path.display()))
+}
+
+#[tauri::command]
+fn delete_employee(
+    state: tauri::State<AppState>,
+    token: String,
+    id: String,
+) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_employee(&tx, &id)?
+        .filter(|e| e.deleted_at.is_none())
+    else {
+        return Err(AppError::not_found(tr!("Empleado no encontrado")));
+    };
+    tx.execute(
+        "UPDATE employees SET deleted_at = datetime('now'), deleted_by = ?1
+         WHERE id = ?2",
+        params![user.username, id],
+    )?;
+    let after = fetch_employee(&tx, &id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "delete_employee",
+        audit::Change {
+            entity: audit::Entity::Employee,
+            entity_id: id.clone(),
+            employee_id: id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Empleado eliminado exitosamente"))
+}
+
+
+#[tauri::command]
+fn get_recycle_bin(state: tauri::State<AppState>, token: String) -> Result<recycle::RecycleBin, AppError> {
+    state.authorize(&token, Permission::DeleteRecords)?;
+    let db = state.db.lock().unwrap();
+    recycle::list(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn restore_record(state: tauri::State<AppState>, token: String, id: i64) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::DeleteRecords)?;
+    let db = state.db.lock().unwrap();
+
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_record(&tx, id)?
+        .filter(|r| r.deleted_at.is_some())
+    else {
+        return Err(AppError::conflict(tr!("El registro no está en la papelera")));
+    };
+    tx.execute(
+        "UPDATE attendance SET deleted_at = NULL, deleted_by = NULL WHERE id = ?1",
+        params![id],
+    )?;
+    let after = fetch_record(&tx, id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "restore_record",
+        audit::Change {
+            entity: audit::Entity::Attendance,
+            entity_id: id.to_string(),
+            employee_id: before.employee_id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Registro restaurado"))
 }
 
+/// Permanently removes a record that is already in the recycle bin.
 #[tauri::command]
-fn delete_employee(state: tauri::State<AppState>, id: String) -> Result<String, String> {
+fn purge_record(state: tauri::State<AppState>, token: String, id: i64) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::DeleteRecords)?;
     let db = state.db.lock().unwrap();
+    backup::create(&db, BackupKind::PreBulk)?;
 
-    let rows_affected = db
-        .execute("DELETE FROM employees WHERE id = ?1", params![id])
-        .map_err(|e| e.to_string())?;
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_record(&tx, id)?
+        .filter(|r| r.deleted_at.is_some())
+    else {
+        return Err(AppError::conflict(tr!("El registro no está en la papelera")));
+    };
+    tx.execute("DELETE FROM attendance WHERE id = ?1", params![id])?;
+
+    audit::record(
+        &tx,
+        &user,
+        "purge_record",
+        audit::Change {
+            entity: audit::Entity::Attendance,
+            entity_id: id.to_string(),
+            employee_id: before.employee_id.clone(),
+            before: audit::snapshot(&before),
+            after: None,
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Registro eliminado definitivamente"))
+}
 
-    if rows_affected == 0 {
-        return Err("Empleado no encontrado".to_string());
+#[tauri::command]
+fn restore_employee(state: tauri::State<AppState>, token: String, id: String) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_employee(&tx, &id)?
+        .filter(|e| e.deleted_at.is_some())
+    else {
+        return Err(AppError::conflict(tr!("El empleado no está en la papelera")));
+    };
+    tx.execute(
+        "UPDATE employees SET deleted_at = NULL, deleted_by = NULL WHERE id = ?1",
+        params![id],
+    )?;
+    let after = fetch_employee(&tx, &id)?;
+
+    audit::record(
+        &tx,
+        &user,
+        "restore_employee",
+        audit::Change {
+            entity: audit::Entity::Employee,
+            entity_id: id.clone(),
+            employee_id: id.clone(),
+            before: audit::snapshot(&before),
+            after: after.as_ref().and_then(audit::snapshot),
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Empleado restaurado"))
+}
+
+/// Permanently removes an employee from the recycle bin. Refused while any
+/// attendance row references the employee.
+#[tauri::command]
+fn purge_employee(state: tauri::State<AppState>, token: String, id: String) -> Result<String, AppError> {
+    let user = state.authorize(&token, Permission::ManageEmployees)?;
+    let db = state.db.lock().unwrap();
+    backup::create(&db, BackupKind::PreBulk)?;
+
+    let tx = db.unchecked_transaction()?;
+    let Some(before) = fetch_employee(&tx, &id)?
+        .filter(|e| e.deleted_at.is_some())
+    else {
+        return Err(AppError::conflict(tr!("El empleado no está en la papelera")));
+    };
+    let records = recycle::attendance_count(&tx, &id)?;
+    if records > 0 {
+        return Err(AppError::conflict(tr!(
+            "El empleado tiene {} registros de asistencia y no puede eliminarse definitivamente",
+            records
+        )));
     }
+    tx.execute(
+        "DELETE FROM schedule_days
+         WHERE schedule_id IN (SELECT id FROM schedules WHERE employee_id = ?1)",
+        params![id],
+    )?;
+    tx.execute("DELETE FROM schedules WHERE employee_id = ?1", params![id])?;
+    tx.execute("DELETE FROM badges WHERE employee_id = ?1", params![id])?;
+    tx.execute("DELETE FROM employees WHERE id = ?1", params![id])?;
+
+    audit::record(
+        &tx,
+        &user,
+        "purge_employee",
+        audit::Change {
+            entity: audit::Entity::Employee,
+            entity_id: id.clone(),
+            employee_id: id.clone(),
+            before: audit::snapshot(&before),
+            after: None,
+            reason: None,
+        },
+    )?;
+    tx.commit()?;
+
+    Ok(tr!("Empleado eliminado definitivamente"))
+}
 
-    Ok("Empleado eliminado exitosamente".to_string())
+#[derive(Serialize)]
+struct AuthStatus {
+    setup_required: bool,
 }
 
+#[derive(Serialize)]
+struct LoginResponse {
+    token: String,
+    user: SessionUser,
+}
 
 #[tauri::command]
-fn verify_admin_password(
+fn get_auth_status(state: tauri::State<AppState>) -> Result<AuthStatus, AppError> {
+    let db = state.db.lock().unwrap();
+    let has_users = users::has_users(&db)?;
+    Ok(AuthStatus {
+        setup_required: !has_users,
+    })
+}
+
+/// First-run setup: creates the owner account and opens a session for it.
+#[tauri::command]
+fn setup_owner_account(
     state: tauri::State<AppState>,
+    username: String,
+    display_name: String,
     password: String,
-) -> Result<bool, String> {
+) -> Result<LoginResponse, AppError> {
     let db = state.db.lock().unwrap();
+    let user = auth::setup_owner(&db, &username, &display_name, &password)?;
+    let token = state.sessions.lock().unwrap().issue(user.clone());
+    Ok(LoginResponse { token, user })
+}
 
-    let stored: String = db
-        .query_row(
-            "SELECT value FROM config WHERE key = 'admin_password'",
-            [],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn admin_login(
+    state: tauri::State<AppState>,
+    username: String,
+    password: String,
+) -> Result<LoginResponse, AppError> {
+    let db = state.db.lock().unwrap();
+    let user = auth::login(&db, &username, &password)?;
+    let token = state.sessions.lock().unwrap().issue(user.clone());
+    Ok(LoginResponse { token, user })
+}
+
+#[tauri::command]
+fn change_admin_password(
+    state: tauri::State<AppState>,
+    token: String,
+    current_password: String,
+    new_password: String,
+) -> Result<String, AppError> {
+    let user = state.current_user(&token)?;
+    let db = state.db.lock().unwrap();
+    auth::change_password(&db, user.id, &current_password, &new_password)?;
 
-    Ok(stored == password)
+    // Other sessions opened with the old password are no longer trusted
+    state.sessions.lock().unwrap().revoke_user(user.id, Some(&token));
+    Ok(tr!("Contraseña actualizada"))
 }
 
 #[tauri::command]
-fn export_to_excel(
+fn get_users(state: tauri::State<AppState>, token: String) -> Result<Vec<UserAccount>, AppError> {
+    state.authorize(&token, Permission::ManageUsers)?;
+    let db = state.db.lock().unwrap();
+    users::get_users(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn create_user(
     state: tauri::State<AppState>,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    employee_id: Option<String>,
-    record_type: Option<String>,
-) -> Result<String, String> {
-    let records = {
-        let db = state.db.lock().unwrap();
+    token: String,
+    username: String,
+    display_name: String,
+    password: String,
+    role: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageUsers)?;
+    let db = state.db.lock().unwrap();
+    users::create_user(&db, &username, &display_name, &password, &role)?;
+    Ok(tr!("Usuario {} creado", username.trim()))
+}
 
-        let mut sql = String::from(
-            "SELECT id, employee_id, employee_name, timestamp, type, notes, created_at, updated_at FROM attendance WHERE 1=1",
-        );
-        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+#[tauri::command]
+fn update_user(
+    state: tauri::State<AppState>,
+    token: String,
+    id: i64,
+    display_name: String,
+    role: String,
+    active: bool,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageUsers)?;
+    let db = state.db.lock().unwrap();
+    users::update_user(&db, id, &display_name, &role, active)?;
+
+    // Open sessions carry the old role; make the user log in again
+    state.sessions.lock().unwrap().revoke_user(id, None);
+    Ok(tr!("Usuario actualizado"))
+}
 
-        build_query_filters(&mut sql, &mut param_values, &start_date, &end_date, &employee_id, &record_type);
-        sql.push_str(" ORDER BY timestamp DESC");
+#[tauri::command]
+fn reset_user_password(
+    state: tauri::State<AppState>,
+    token: String,
+    id: i64,
+    new_password: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageUsers)?;
+    let db = state.db.lock().unwrap();
+    users::reset_password(&db, id, &new_password)?;
+    state.sessions.lock().unwrap().revoke_user(id, None);
+    Ok(tr!("Contraseña restablecida"))
+}
 
-        let params_refs: Vec<&dyn rusqlite::types::ToSql> =
-            param_values.iter().map(|p| p.as_ref()).collect();
+#[tauri::command]
+fn admin_logout(state: tauri::State<AppState>, token: String) {
+    state.sessions.lock().unwrap().revoke(&token);
+}
 
-        let mut stmt = db.prepare(&sql).map_err(|e| e.to_string())?;
-        let result = stmt
-            .query_map(params_refs.as_slice(), row_to_record)
-            .map_err(|e| e.to_string())?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
-        result
+#[tauri::command]
+fn export_to_excel(
+    state: tauri::State<AppState>,
+    token: String,
+    filters: RecordFilters,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ExportRecords)?;
+    validate_filters(&filters)?;
+    let (records, boundary, corrections) = {
+        let db = state.db.lock().unwrap();
+        let records = query_records(&db, &filters)?;
+        let boundary = WorkdayBoundary::load(&db)?;
+        let corrections = audit::corrections(&db)?;
+        (records, boundary, corrections)
     };
 
     let mut workbook = Workbook::new();
@@ -521,68 +1671,316 @@ fn export_to_excel(
 
     let header_format = Format::new().set_bold();
 
-    let headers = ["ID", "Empleado ID", "Nombre", "Fecha", "Hora", "Tipo", "Notas"];
+    let headers = [
+        "ID", "Empleado ID", "Nombre", "Fecha", "Hora", "Tipo", "Notas", "Jornada", "Origen",
+        "Dispositivo", "Corrección",
+    ]
+    .map(i18n::translate);
     for (col, header) in headers.iter().enumerate() {
-        worksheet
-            .write_string_with_format(0, col as u16, *header, &header_format)
-            .map_err(|e| e.to_string())?;
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
     }
 
     for (row_idx, record) in records.iter().enumerate() {
         let row = (row_idx + 1) as u32;
-        worksheet.write_number(row, 0, record.id as f64).map_err(|e| e.to_string())?;
-        worksheet.write_string(row, 1, &record.employee_id).map_err(|e| e.to_string())?;
-        worksheet
-            .write_string(row, 2, record.employee_name.as_deref().unwrap_or(""))
-            .map_err(|e| e.to_string())?;
+        worksheet.write_number(row, 0, record.id as f64)?;
+        worksheet.write_string(row, 1, &record.employee_id)?;
+        worksheet.write_string(row, 2, record.employee_name.as_deref().unwrap_or(""))?;
 
         // Split timestamp into date and time
         let parts: Vec<&str> = record.timestamp.splitn(2, ' ').collect();
         let date_str = parts.first().unwrap_or(&"");
         let time_str = parts.get(1).unwrap_or(&"");
 
-        worksheet.write_string(row, 3, *date_str).map_err(|e| e.to_string())?;
-        worksheet.write_string(row, 4, *time_str).map_err(|e| e.to_string())?;
-
-        let tipo = match record.r#type.as_str() {
-            "entry" => "Entrada",
-            "exit" => "Salida",
-            other => other,
-        };
-        worksheet.write_string(row, 5, tipo).map_err(|e| e.to_string())?;
-        worksheet
-            .write_string(row, 6, record.notes.as_deref().unwrap_or(""))
-            .map_err(|e| e.to_string())?;
+        worksheet.write_string(row, 3, *date_str)?;
+        worksheet.write_string(row, 4, *time_str)?;
+
+        let tipo = PunchType::parse(&record.r#type)
+            .map(|t| t.label())
+            .unwrap_or(&record.r#type);
+        worksheet.write_string(row, 5, tipo)?;
+        worksheet.write_string(row, 6, record.notes.as_deref().unwrap_or(""))?;
+
+        // Workday the punch belongs to, which differs from the date for night shifts
+        let workday = timesheet::parse_timestamp(&record.timestamp)
+            .map(|ts| boundary.business_date(ts).format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        worksheet.write_string(row, 7, &workday)?;
+
+        let origin = RecordSource::parse(&record.source)
+            .map(|s| s.label())
+            .unwrap_or(&record.source);
+        worksheet.write_string(row, 8, origin)?;
+        worksheet.write_string(row, 9, record.device_id.as_deref().unwrap_or(""))?;
+
+        // Edited rows carry the reason given for the correction, if any
+        if let Some(reason) = corrections.get(&record.id) {
+            let edited = tr!("Editado");
+            worksheet.write_string(row, 10, reason.as_deref().unwrap_or(&edited))?;
+        }
     }
 
     // Set column widths
-    worksheet.set_column_width(0, 8).map_err(|e| e.to_string())?;
-    worksheet.set_column_width(1, 15).map_err(|e| e.to_string())?;
-    worksheet.set_column_width(2, 25).map_err(|e| e.to_string())?;
-    worksheet.set_column_width(3, 12).map_err(|e| e.to_string())?;
-    worksheet.set_column_width(4, 10).map_err(|e| e.to_string())?;
-    worksheet.set_column_width(5, 10).map_err(|e| e.to_string())?;
-    worksheet.set_column_width(6, 30).map_err(|e| e.to_string())?;
-
-    let today = Local::now().format("%Y-%m-%d").to_string();
-    let filename = format!("Asistencia_{}.xlsx", today);
-
-    // Try Desktop first, fallback to exe directory
-    let export_path = if let Some(desktop) = dirs_desktop() {
-        desktop.join(&filename)
-    } else if let Ok(exe_dir) = std::env::current_exe() {
-        exe_dir.parent().unwrap_or(std::path::Path::new(".")).join(&filename)
-    } else {
-        std::path::PathBuf::from(&filename)
+    worksheet.set_column_width(0, 8)?;
+    worksheet.set_column_width(1, 15)?;
+    worksheet.set_column_width(2, 25)?;
+    worksheet.set_column_width(3, 12)?;
+    worksheet.set_column_width(4, 10)?;
+    worksheet.set_column_width(5, 10)?;
+    worksheet.set_column_width(6, 30)?;
+    worksheet.set_column_width(7, 12)?;
+    worksheet.set_column_width(8, 16)?;
+    worksheet.set_column_width(9, 16)?;
+    worksheet.set_column_width(10, 40)?;
+
+    let today = timezone::now_local().format("%Y-%m-%d").to_string();
+    let export_path = export_path(&tr!("Asistencia_{}.xlsx", today));
+
+    workbook.save(&export_path)?;
+
+    Ok(export_path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn get_clock_anomalies(
+    state: tauri::State<AppState>,
+    token: String,
+) -> Result<Vec<clock::ClockAnomaly>, AppError> {
+    state.authorize(&token, Permission::ViewAudit)?;
+    let db = state.db.lock().unwrap();
+    clock::anomalies(&db).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_audit_log(
+    state: tauri::State<AppState>,
+    token: String,
+    record_id: Option<i64>,
+    employee_id: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<audit::AuditEntry>, AppError> {
+    state.authorize(&token, Permission::ViewAudit)?;
+    let db = state.db.lock().unwrap();
+    let filter = audit::AuditFilter {
+        record_id,
+        employee_id,
+        start_date,
+        end_date,
     };
+    audit::query(&db, &filter).map_err(AppError::from)
+}
 
-    workbook
-        .save(&export_path)
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn export_audit_log(
+    state: tauri::State<AppState>,
+    token: String,
+    record_id: Option<i64>,
+    employee_id: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ViewAudit)?;
+    let entries = {
+        let db = state.db.lock().unwrap();
+        let filter = audit::AuditFilter {
+            record_id,
+            employee_id,
+            start_date,
+            end_date,
+        };
+        audit::query(&db, &filter)?
+    };
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+
+    let header_format = Format::new().set_bold();
+
+    let headers = [
+        "ID", "Fecha", "Usuario", "Comando", "Entidad", "Registro", "Empleado ID", "Antes", "Después",
+        "Motivo",
+    ]
+    .map(i18n::translate);
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+
+    for (row_idx, entry) in entries.iter().enumerate() {
+        let row = (row_idx + 1) as u32;
+        let json = |v: &Option<serde_json::Value>| v.as_ref().map(|v| v.to_string()).unwrap_or_default();
+        worksheet.write_number(row, 0, entry.id as f64)?;
+        worksheet.write_string(row, 1, &entry.timestamp)?;
+        worksheet.write_string(row, 2, &entry.actor)?;
+        worksheet.write_string(row, 3, &entry.command)?;
+        worksheet.write_string(row, 4, &entry.entity)?;
+        worksheet.write_string(row, 5, &entry.entity_id)?;
+        worksheet.write_string(row, 6, &entry.employee_id)?;
+        worksheet.write_string(row, 7, json(&entry.before))?;
+        worksheet.write_string(row, 8, json(&entry.after))?;
+        worksheet.write_string(row, 9, entry.reason.as_deref().unwrap_or(""))?;
+    }
+
+    worksheet.set_column_width(0, 8)?;
+    worksheet.set_column_width(1, 20)?;
+    worksheet.set_column_width(2, 15)?;
+    worksheet.set_column_width(3, 18)?;
+    worksheet.set_column_width(4, 12)?;
+    worksheet.set_column_width(5, 10)?;
+    worksheet.set_column_width(6, 15)?;
+    worksheet.set_column_width(7, 60)?;
+    worksheet.set_column_width(8, 60)?;
+    worksheet.set_column_width(9, 40)?;
+
+    let today = timezone::now_local().format("%Y-%m-%d").to_string();
+    let export_path = export_path(&tr!("Auditoria_{}.xlsx", today));
+
+    workbook.save(&export_path)?;
 
     Ok(export_path.to_string_lossy().to_string())
 }
 
+#[tauri::command]
+fn get_backups(state: tauri::State<AppState>, token: String) -> Result<Vec<BackupInfo>, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    backup::list(&db)
+}
+
+#[tauri::command]
+fn create_backup(state: tauri::State<AppState>, token: String) -> Result<BackupInfo, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    backup::create(&db, BackupKind::Manual)
+}
+
+#[tauri::command]
+fn restore_backup(state: tauri::State<AppState>, token: String, name: String) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let mut db = state.db.lock().unwrap();
+    let key = state.key.lock().unwrap().clone();
+    let restored = backup::restore(&mut db, key.as_deref(), &name)?;
+    // The backup may predate the current schema
+    init_database(&db)?;
+    // Accounts may differ in the restored database
+    state.sessions.lock().unwrap().revoke_all();
+    Ok(tr!(
+        "Respaldo del {} restaurado. Inicia sesión nuevamente.",
+        restored.created_at
+    ))
+}
+
+#[tauri::command]
+fn get_backup_settings(state: tauri::State<AppState>, token: String) -> Result<BackupSettings, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    Ok(backup::settings(&db))
+}
+
+#[tauri::command]
+fn set_backup_settings(
+    state: tauri::State<AppState>,
+    token: String,
+    settings: BackupSettings,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    let db = state.db.lock().unwrap();
+    backup::save_settings(&db, &settings)?;
+    Ok(tr!("Configuración de respaldos actualizada"))
+}
+
+/// How startup went, plus the last automatic backup failure once the app runs.
+fn startup_status(app: &tauri::AppHandle, recovery: &Recovery) -> RecoveryStatus {
+    let mut status = recovery.status();
+    if let Some(state) = app.try_state::<AppState>() {
+        status.backup_error = state.backup_error.lock().unwrap().clone();
+    }
+    status
+}
+
+#[tauri::command]
+fn get_recovery_status(app: tauri::AppHandle, recovery: tauri::State<Recovery>) -> RecoveryStatus {
+    startup_status(&app, &recovery)
+}
+
+#[tauri::command]
+fn unlock_database(
+    app: tauri::AppHandle,
+    recovery: tauri::State<Recovery>,
+    secret: String,
+) -> Result<RecoveryStatus, AppError> {
+    if !matches!(*recovery.startup.lock().unwrap(), Startup::Locked) {
+        return Err(AppError::conflict(tr!("La base de datos no está bloqueada")));
+    }
+    // A wrong secret leaves the database locked
+    let db_path = recovery.db_path()?;
+    storage::open_read_only(db_path, Some(&secret))?;
+
+    let startup = start(&app, db_path, Some(secret.clone()));
+    *recovery.key.lock().unwrap() = Some(secret);
+    *recovery.startup.lock().unwrap() = startup;
+    Ok(startup_status(&app, &recovery))
+}
+
+/// Sets the secret that authorizes recovery actions when no account can be
+/// checked.
+#[tauri::command]
+fn set_recovery_secret(
+    state: tauri::State<AppState>,
+    recovery: tauri::State<Recovery>,
+    token: String,
+    secret: String,
+) -> Result<String, AppError> {
+    state.authorize(&token, Permission::ManageSettings)?;
+    recovery::set_secret(&recovery.config_dir, &secret)?;
+    Ok(tr!("Clave de recuperación guardada"))
+}
+
+fn require_recovery_mode(recovery: &Recovery) -> Result<(), AppError> {
+    if !recovery.is_failed() {
+        return Err(AppError::conflict(tr!("La aplicación no está en modo de recuperación")));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn restore_latest_backup(
+    app: tauri::AppHandle,
+    recovery: tauri::State<Recovery>,
+    username: String,
+    password: String,
+) -> Result<String, AppError> {
+    require_recovery_mode(&recovery)?;
+    recovery::authorize(&recovery, &username, &password)?;
+    recovery::restore_latest_backup(recovery.db_path()?, recovery.key().as_deref())?;
+    // Start over so the restored database goes through the startup checks
+    app.restart()
+}
+
+#[tauri::command]
+fn export_salvage(
+    recovery: tauri::State<Recovery>,
+    username: String,
+    password: String,
+) -> Result<String, AppError> {
+    require_recovery_mode(&recovery)?;
+    recovery::authorize(&recovery, &username, &password)?;
+    let today = timezone::now_local().format("%Y-%m-%d").to_string();
+    let path = export_path(&tr!("Recuperacion_{}.xlsx", today));
+    let rows = recovery::export_salvage(recovery.db_path()?, recovery.key().as_deref(), &path)?;
+    Ok(tr!("{} filas exportadas a {}", rows, path.display()))
+}
+
+/// Desktop first, falling back to the executable's directory.
+fn export_path(filename: &str) -> std::path::PathBuf {
+    if let Some(desktop) = dirs_desktop() {
+        desktop.join(filename)
+    } else if let Ok(exe_dir) = std::env::current_exe() {
+        exe_dir.parent().unwrap_or(std::path::Path::new(".")).join(filename)
+    } else {
+        std::path::PathBuf::from(filename)
+    }
+}
+
 fn dirs_desktop() -> Option<std::path::PathBuf> {
     #[cfg(target_os = "windows")]
     {
@@ -601,27 +1999,108 @@ fn dirs_desktop() -> Option<std::path::PathBuf> {
 }
 
 fn main() {
-    let conn = Connection::open("attendance.db").unwrap();
-    init_database(&conn);
-    
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(AppState {
-            db: Mutex::new(conn),
+        .setup(|app| {
+            let paths = app.path();
+            let config_dir = paths.app_config_dir()?;
+            // An encrypted database waits for its secret unless it was given
+            // in the environment, and one that cannot be found, opened or
+            // checked starts the app in recovery mode instead of the kiosk
+            let (db_path, key, startup) = match storage::resolve_path(&paths.app_data_dir()?, &config_dir) {
+                Ok(db_path) => {
+                    let key = storage::is_encrypted(&db_path).then(storage::env_key).flatten();
+                    let startup = if storage::is_encrypted(&db_path) && key.is_none() {
+                        Startup::Locked
+                    } else {
+                        start(app.handle(), &db_path, key.clone())
+                    };
+                    (Some(db_path), key, startup)
+                }
+                Err(e) => (None, None, Startup::Failed(vec![e.to_string()])),
+            };
+            app.manage(Recovery {
+                db_path,
+                config_dir,
+                startup: Mutex::new(startup),
+                key: Mutex::new(key),
+            });
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             check_in,
             check_out,
+            punch_by_badge,
+            get_presence_state,
+            get_pin_required,
+            set_pin_required,
+            get_sequence_policy,
+            set_sequence_policy,
+            get_workday_start,
+            set_workday_start,
+            get_locale,
+            set_locale,
+            get_timezone,
+            get_timezones,
+            set_timezone,
+            get_device_id,
+            set_device_id,
+            get_database_path,
+            get_encryption_status,
+            encrypt_database,
             get_records,
             get_daily_stats,
+            get_timesheet,
+            get_tardiness_report,
+            get_absences,
+            get_holidays,
+            add_holiday,
+            delete_holiday,
+            get_employee_schedules,
+            set_employee_schedule,
+            delete_employee_schedule,
+            get_grace_minutes,
+            set_grace_minutes,
+            create_manual_record,
             update_record,
             delete_record,
             get_employees,
             create_employee,
             update_employee,
+            reset_employee_pin,
+            get_badges,
+            assign_badge,
+            revoke_badge,
+            export_badge_sheet,
             delete_employee,
-            verify_admin_password,
+            get_recycle_bin,
+            restore_record,
+            purge_record,
+            restore_employee,
+            purge_employee,
+            get_auth_status,
+            setup_owner_account,
+            admin_login,
+            change_admin_password,
+            get_users,
+            create_user,
+            update_user,
+            reset_user_password,
+            admin_logout,
             export_to_excel,
+            get_audit_log,
+            get_clock_anomalies,
+            export_audit_log,
+            get_backups,
+            create_backup,
+            restore_backup,
+            get_backup_settings,
+            set_backup_settings,
+            get_recovery_status,
+            unlock_database,
+            restore_latest_backup,
+            export_salvage,
+            set_recovery_secret,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,441 @@
+// Schema migrations. The schema version lives in `PRAGMA user_version`; each
+// entry in MIGRATIONS moves the database from `version - 1` to `version` and
+// runs in its own transaction together with the version bump, so a failed
+// migration leaves the database at the last version that applied cleanly.
+//
+// Before an existing database is migrated it is backed up, and a database
+// written by a newer build is refused instead of being opened.
+
+use crate::backup::{self, BackupKind};
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::timesheet::parse_timestamp;
+use crate::timezone;
+use chrono_tz::Tz;
+use rusqlite::{params, Connection, OptionalExtension};
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    up: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "esquema base",
+        up: baseline,
+    },
+    Migration {
+        version: 2,
+        description: "marcas de tiempo en UTC",
+        up: utc_timestamps,
+    },
+    Migration {
+        version: 3,
+        description: "anomalías del reloj",
+        up: clock_anomalies,
+    },
+    Migration {
+        version: 4,
+        description: "PIN de empleados",
+        up: employee_pins,
+    },
+    Migration {
+        version: 5,
+        description: "gafetes",
+        up: badges,
+    },
+];
+
+/// Schema version this build writes.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+fn is_empty(conn: &Connection) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT NOT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table')",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Brings the database up to `latest_version`, backing it up first when it
+/// already holds data.
+pub fn run(conn: &Connection) -> Result<(), AppError> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(AppError::conflict(tr!(
+            "La base de datos tiene la versión de esquema {} pero esta versión de la aplicación solo admite hasta la {}. Actualiza la aplicación.",
+            current, latest
+        )));
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    if !is_empty(conn)? {
+        backup::create(conn, BackupKind::PreMigration)
+            .map_err(|e| AppError::database(tr!("No se pudo respaldar la base de datos antes de migrarla"), e))?;
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        apply(conn, migration).map_err(|e| {
+            AppError::database(
+                tr!("Error al aplicar la migración {} ({})", migration.version, migration.description),
+                e,
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn apply(conn: &Connection, migration: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    (migration.up)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        &format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table),
+        [column],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|found| found.is_some())
+}
+
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+/// Version 1: the schema as it stood before versioning. Databases created by
+/// earlier builds may lack any of the columns added over time, so those are
+/// added only where missing. SQLite cannot add a column with a non-constant
+/// default, so the attendance timestamps are added bare and backfilled.
+fn baseline(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS attendance (
+            id INTEGER PRIMARY KEY,
+            employee_id TEXT NOT NULL,
+            employee_name TEXT,
+            timestamp DATETIME DEFAULT (datetime('now')),
+            type TEXT CHECK(type IN ('entry', 'exit')),
+            notes TEXT,
+            flag TEXT,
+            source TEXT NOT NULL DEFAULT 'kiosk',
+            device_id TEXT,
+            created_at DATETIME DEFAULT (datetime('now')),
+            updated_at DATETIME DEFAULT (datetime('now')),
+            deleted_at DATETIME,
+            deleted_by TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS employees (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            active BOOLEAN DEFAULT 1,
+            created_at DATETIME DEFAULT (datetime('now')),
+            updated_at DATETIME DEFAULT (datetime('now')),
+            deleted_at DATETIME,
+            deleted_by TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS schedules (
+            id INTEGER PRIMARY KEY,
+            employee_id TEXT NOT NULL,
+            effective_from DATE NOT NULL,
+            created_at DATETIME DEFAULT (datetime('now')),
+            UNIQUE(employee_id, effective_from)
+        );
+
+        CREATE TABLE IF NOT EXISTS schedule_days (
+            schedule_id INTEGER NOT NULL,
+            weekday INTEGER NOT NULL CHECK(weekday BETWEEN 0 AND 6),
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            PRIMARY KEY (schedule_id, weekday)
+        );
+
+        CREATE TABLE IF NOT EXISTS holidays (
+            date DATE PRIMARY KEY,
+            name TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY,
+            username TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            display_name TEXT NOT NULL,
+            password_hash TEXT NOT NULL,
+            role TEXT NOT NULL CHECK(role IN ('owner', 'admin', 'supervisor', 'auditor')),
+            active BOOLEAN DEFAULT 1,
+            created_at DATETIME DEFAULT (datetime('now')),
+            updated_at DATETIME DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY,
+            timestamp DATETIME NOT NULL,
+            actor_id INTEGER,
+            actor TEXT NOT NULL,
+            command TEXT NOT NULL,
+            entity TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            employee_id TEXT,
+            before_json TEXT,
+            after_json TEXT,
+            reason TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS config (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )?;
+
+    for (table, column, definition) in [
+        ("attendance", "notes", "TEXT"),
+        ("attendance", "created_at", "DATETIME"),
+        ("attendance", "updated_at", "DATETIME"),
+        ("attendance", "flag", "TEXT"),
+        ("attendance", "deleted_at", "DATETIME"),
+        ("attendance", "deleted_by", "TEXT"),
+        ("attendance", "source", "TEXT NOT NULL DEFAULT 'kiosk'"),
+        ("attendance", "device_id", "TEXT"),
+        ("employees", "deleted_at", "DATETIME"),
+        ("employees", "deleted_by", "TEXT"),
+    ] {
+        add_column(conn, table, column, definition)?;
+    }
+    conn.execute_batch(
+        "UPDATE attendance SET created_at = timestamp WHERE created_at IS NULL;
+         UPDATE attendance SET updated_at = created_at WHERE updated_at IS NULL;",
+    )?;
+
+    // Early builds created an idx_date index that causes errors on some
+    // platforms.
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_date;
+
+        CREATE INDEX IF NOT EXISTS idx_employee_id ON attendance(employee_id);
+        CREATE INDEX IF NOT EXISTS idx_timestamp ON attendance(timestamp);
+        CREATE INDEX IF NOT EXISTS idx_type ON attendance(type);
+        CREATE INDEX IF NOT EXISTS idx_source ON attendance(source);
+        CREATE INDEX IF NOT EXISTS idx_employee_active ON employees(active);
+        CREATE INDEX IF NOT EXISTS idx_audit_timestamp ON audit_log(timestamp);
+        CREATE INDEX IF NOT EXISTS idx_audit_entity ON audit_log(entity, entity_id);
+        CREATE INDEX IF NOT EXISTS idx_audit_employee ON audit_log(employee_id);",
+    )
+}
+
+/// Rewrites a column holding site wall-clock times as UTC. Values that do not
+/// parse, or fall in a DST gap, are left as they are.
+fn local_to_utc(conn: &Connection, tz: Tz, table: &str, column: &str, filter: &str) -> rusqlite::Result<()> {
+    let rows: Vec<(rusqlite::types::Value, String)> = {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, {column} FROM {table} WHERE {column} IS NOT NULL{filter}"
+        ))?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let mut update = conn.prepare(&format!("UPDATE {table} SET {column} = ?1 WHERE id = ?2"))?;
+    for (id, value) in rows {
+        if let Some(instant) = parse_timestamp(&value).and_then(|local| timezone::to_utc_in(tz, local)) {
+            update.execute(params![timezone::format_utc(instant), id])?;
+        }
+    }
+    Ok(())
+}
+
+/// Version 2: punches, edits, deletions and audit entries used to be written
+/// in the computer's local time while other defaults were UTC. They are
+/// converted to UTC using the configured site timezone. Employee rows only got
+/// a local `updated_at` when edited, so an untouched one is left alone.
+fn utc_timestamps(conn: &Connection) -> rusqlite::Result<()> {
+    let tz = timezone::configured(conn)?;
+    for (table, column, filter) in [
+        ("attendance", "timestamp", ""),
+        ("attendance", "created_at", ""),
+        ("attendance", "updated_at", ""),
+        ("attendance", "deleted_at", ""),
+        ("employees", "updated_at", " AND updated_at <> created_at"),
+        ("employees", "deleted_at", ""),
+        ("audit_log", "timestamp", ""),
+    ] {
+        local_to_utc(conn, tz, table, column, filter)?;
+    }
+    Ok(())
+}
+
+/// Version 3: log of wall-clock jumps seen at the kiosk.
+fn clock_anomalies(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS clock_anomalies (
+            id INTEGER PRIMARY KEY,
+            kind TEXT NOT NULL CHECK(kind IN ('backward', 'forward')),
+            expected_at DATETIME NOT NULL,
+            detected_at DATETIME NOT NULL,
+            employee_id TEXT NOT NULL,
+            record_id INTEGER
+        );",
+    )
+}
+
+/// Version 4: optional kiosk PIN per employee and its failed-attempt lockout.
+fn employee_pins(conn: &Connection) -> rusqlite::Result<()> {
+    for (column, definition) in [
+        ("pin_hash", "TEXT"),
+        ("pin_failed_attempts", "INTEGER NOT NULL DEFAULT 0"),
+        ("pin_locked_until", "DATETIME"),
+    ] {
+        add_column(conn, "employees", column, definition)?;
+    }
+    Ok(())
+}
+
+/// Version 5: badge codes scanned at the kiosk.
+fn badges(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS badges (
+            code TEXT PRIMARY KEY,
+            employee_id TEXT NOT NULL,
+            label TEXT,
+            created_at DATETIME DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_badge_employee ON badges(employee_id);",
+    )
+}
+
+/// Fresh in-memory database at the latest version, for tests.
+#[cfg(test)]
+pub(crate) fn test_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    run(&conn).unwrap();
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .unwrap();
+        let names = stmt.query_map([], |row| row.get(0)).unwrap();
+        names.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn versions_are_consecutive() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, i + 1);
+        }
+    }
+
+    #[test]
+    fn new_database_reaches_the_latest_version() {
+        let conn = test_db();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        let tables = ["attendance", "employees", "users", "audit_log", "clock_anomalies", "badges"];
+        for table in tables {
+            assert!(!columns(&conn, table).is_empty(), "{} is missing", table);
+        }
+        assert!(columns(&conn, "employees").contains(&"pin_hash".to_string()));
+    }
+
+    #[test]
+    fn running_again_is_a_no_op() {
+        let conn = test_db();
+        conn.execute("INSERT INTO employees (id, name) VALUES ('001', 'Ana')", []).unwrap();
+        // With data in place a second migration would need a backup, which an
+        // in-memory database cannot take
+        run(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+        let e = run(&conn).unwrap_err();
+        assert_eq!(e.code(), "CONFLICT");
+    }
+
+    #[test]
+    fn baseline_adds_columns_missing_from_old_databases() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE attendance (
+                id INTEGER PRIMARY KEY,
+                employee_id TEXT NOT NULL,
+                employee_name TEXT,
+                timestamp DATETIME,
+                type TEXT
+            );
+            CREATE TABLE employees (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                active BOOLEAN DEFAULT 1,
+                created_at DATETIME,
+                updated_at DATETIME
+            );
+            INSERT INTO attendance (employee_id, employee_name, timestamp, type)
+            VALUES ('001', 'Ana', '2024-01-15 08:00:00', 'entry');",
+        )
+        .unwrap();
+
+        apply(&conn, &MIGRATIONS[0]).unwrap();
+        let attendance = columns(&conn, "attendance");
+        for column in ["notes", "flag", "source", "device_id", "deleted_at", "created_at"] {
+            assert!(attendance.contains(&column.to_string()), "{} is missing", column);
+        }
+        let (created_at, source): (String, String) = conn
+            .query_row("SELECT created_at, source FROM attendance", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(created_at, "2024-01-15 08:00:00");
+        assert_eq!(source, "kiosk");
+        assert_eq!(current_version(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn utc_timestamps_converts_local_times_with_the_site_timezone() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply(&conn, &MIGRATIONS[0]).unwrap();
+        conn.execute_batch(
+            "INSERT INTO config (key, value) VALUES ('timezone', 'America/Mexico_City');
+             INSERT INTO attendance (employee_id, type, timestamp, created_at, updated_at)
+             VALUES ('001', 'entry', '2024-01-15 08:00:00', '2024-01-15 08:00:00',
+                     '2024-01-15 08:00:00');
+             INSERT INTO employees (id, name, created_at, updated_at)
+             VALUES ('001', 'Ana', '2024-01-01 12:00:00', '2024-01-01 12:00:00');",
+        )
+        .unwrap();
+
+        apply(&conn, &MIGRATIONS[1]).unwrap();
+        let timestamp: String = conn
+            .query_row("SELECT timestamp FROM attendance", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(timestamp, "2024-01-15 14:00:00");
+        // Never edited, so already UTC
+        let updated_at: String = conn
+            .query_row("SELECT updated_at FROM employees", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(updated_at, "2024-01-01 12:00:00");
+    }
+}
@@ -0,0 +1,198 @@
+// Employee PINs: when enabled in the settings, kiosk punches must carry the
+// employee's PIN so nobody can punch for a coworker. PINs are kept only as
+// Argon2 hashes in `employees`. Too many wrong PINs in a row lock the employee
+// out for a while; the counter is stored with the employee so restarting the
+// app does not clear it.
+
+use crate::auth;
+use crate::config;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::timezone;
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+const REQUIRED_KEY: &str = "require_employee_pin";
+const MIN_PIN_LENGTH: usize = 4;
+const MAX_PIN_LENGTH: usize = 8;
+const MAX_ATTEMPTS: i64 = 5;
+const LOCKOUT_MINUTES: i64 = 15;
+
+pub fn is_required(conn: &Connection) -> rusqlite::Result<bool> {
+    Ok(config::get(conn, REQUIRED_KEY)?
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(false))
+}
+
+pub fn set_required(conn: &Connection, required: bool) -> rusqlite::Result<()> {
+    config::set(conn, REQUIRED_KEY, &required.to_string())
+}
+
+fn validate_new_pin(pin: &str) -> Result<(), AppError> {
+    let valid = (MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&pin.len())
+        && pin.chars().all(|c| c.is_ascii_digit());
+    if !valid {
+        return Err(AppError::validation(tr!(
+            "El PIN debe tener entre {} y {} dígitos",
+            MIN_PIN_LENGTH,
+            MAX_PIN_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Sets a new PIN, or removes it when `pin` is `None`, and clears any lockout.
+pub fn reset(conn: &Connection, employee_id: &str, pin: Option<&str>) -> Result<(), AppError> {
+    let hash = match pin {
+        Some(pin) => {
+            validate_new_pin(pin)?;
+            Some(auth::hash_password(pin)?)
+        }
+        None => None,
+    };
+    conn.execute(
+        "UPDATE employees SET pin_hash = ?1, pin_failed_attempts = 0, pin_locked_until = NULL,
+                updated_at = datetime('now')
+         WHERE id = ?2",
+        params![hash, employee_id],
+    )?;
+    Ok(())
+}
+
+/// Checks the PIN given at the kiosk when PINs are required. Every wrong PIN
+/// counts toward the lockout, and a right one resets the count.
+pub fn verify(conn: &Connection, employee_id: &str, pin: Option<&str>) -> Result<(), AppError> {
+    if !is_required(conn)? {
+        return Ok(());
+    }
+
+    let account: Option<(Option<String>, i64, Option<String>)> = conn
+        .query_row(
+            "SELECT pin_hash, pin_failed_attempts, pin_locked_until FROM employees
+             WHERE id = ?1 AND deleted_at IS NULL",
+            params![employee_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let Some((Some(hash), failed_attempts, locked_until)) = account else {
+        return Err(AppError::auth(tr!(
+            "El empleado no tiene PIN asignado, solicítalo a un administrador"
+        )));
+    };
+
+    let now = Utc::now();
+    if let Some(until) = locked_until.as_deref().and_then(timezone::parse_utc) {
+        if until > now {
+            let minutes = (until - now).num_minutes() + 1;
+            return Err(AppError::auth(tr!(
+                "Demasiados intentos fallidos, intenta de nuevo en {} minutos",
+                minutes
+            )));
+        }
+    }
+
+    let pin = pin.map(str::trim).unwrap_or_default();
+    if pin.is_empty() {
+        return Err(AppError::auth(tr!("Ingresa tu PIN")));
+    }
+    if auth::verify_password(pin, &hash) {
+        conn.execute(
+            "UPDATE employees SET pin_failed_attempts = 0, pin_locked_until = NULL WHERE id = ?1",
+            params![employee_id],
+        )?;
+        return Ok(());
+    }
+
+    let failed_attempts = failed_attempts + 1;
+    if failed_attempts >= MAX_ATTEMPTS {
+        let until = timezone::format_utc(now + Duration::minutes(LOCKOUT_MINUTES));
+        conn.execute(
+            "UPDATE employees SET pin_failed_attempts = 0, pin_locked_until = ?1 WHERE id = ?2",
+            params![until, employee_id],
+        )?;
+        return Err(AppError::auth(tr!(
+            "Demasiados intentos fallidos, intenta de nuevo en {} minutos",
+            LOCKOUT_MINUTES
+        )));
+    }
+    conn.execute(
+        "UPDATE employees SET pin_failed_attempts = ?1 WHERE id = ?2",
+        params![failed_attempts, employee_id],
+    )?;
+    Err(AppError::auth(tr!(
+        "PIN incorrecto, te quedan {} intentos",
+        MAX_ATTEMPTS - failed_attempts
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    fn required_for_ana(conn: &Connection) {
+        conn.execute("INSERT INTO employees (id, name) VALUES ('001', 'Ana')", []).unwrap();
+        set_required(conn, true).unwrap();
+    }
+
+    fn code(result: Result<(), AppError>) -> &'static str {
+        result.unwrap_err().code()
+    }
+
+    #[test]
+    fn not_required_skips_the_check() {
+        let conn = migrations::test_db();
+        assert!(verify(&conn, "001", None).is_ok());
+    }
+
+    #[test]
+    fn employee_without_pin_is_refused() {
+        let conn = migrations::test_db();
+        required_for_ana(&conn);
+        assert_eq!(code(verify(&conn, "001", Some("1234"))), "AUTH");
+    }
+
+    #[test]
+    fn new_pin_must_be_digits_of_the_right_length() {
+        let conn = migrations::test_db();
+        required_for_ana(&conn);
+        for pin in ["123", "123456789", "12a4"] {
+            assert_eq!(code(reset(&conn, "001", Some(pin))), "VALIDATION");
+        }
+        reset(&conn, "001", Some("1234")).unwrap();
+        assert!(verify(&conn, "001", Some("1234")).is_ok());
+        assert_eq!(code(verify(&conn, "001", None)), "AUTH");
+    }
+
+    #[test]
+    fn wrong_pins_lock_the_employee_out() {
+        let conn = migrations::test_db();
+        required_for_ana(&conn);
+        reset(&conn, "001", Some("1234")).unwrap();
+        for _ in 0..MAX_ATTEMPTS {
+            assert_eq!(code(verify(&conn, "001", Some("0000"))), "AUTH");
+        }
+        // Locked even with the right PIN
+        assert_eq!(code(verify(&conn, "001", Some("1234"))), "AUTH");
+
+        reset(&conn, "001", Some("5678")).unwrap();
+        assert!(verify(&conn, "001", Some("5678")).is_ok());
+    }
+
+    #[test]
+    fn right_pin_resets_the_count() {
+        let conn = migrations::test_db();
+        required_for_ana(&conn);
+        reset(&conn, "001", Some("1234")).unwrap();
+        for _ in 0..MAX_ATTEMPTS - 1 {
+            assert!(verify(&conn, "001", Some("0000")).is_err());
+        }
+        verify(&conn, "001", Some("1234")).unwrap();
+        let failed: i64 = conn
+            .query_row("SELECT pin_failed_attempts FROM employees WHERE id = '001'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(failed, 0);
+    }
+}
@@ -0,0 +1,530 @@
+// Presence tracking: an employee's state is derived from their latest attendance row,
+// and every punch is checked against it before being recorded.
+
+use crate::clock::{self, ClockJump};
+use crate::config;
+use crate::error::AppError;
+use crate::i18n::{self, tr};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+const POLICY_KEY: &str = "punch_sequence_policy";
+const DEVICE_ID_KEY: &str = "device_id";
+
+pub const FLAG_OUT_OF_SEQUENCE: &str = "out_of_sequence";
+pub const FLAG_PLACEHOLDER: &str = "placeholder";
+pub const FLAG_CLOCK_ANOMALY: &str = "clock_anomaly";
+
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceState {
+    Present,
+    Absent,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PunchType {
+    Entry,
+    Exit,
+}
+
+impl PunchType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PunchType::Entry => "entry",
+            PunchType::Exit => "exit",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        i18n::translate(match self {
+            PunchType::Entry => "Entrada",
+            PunchType::Exit => "Salida",
+        })
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "entry" => Some(PunchType::Entry),
+            "exit" => Some(PunchType::Exit),
+            _ => None,
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            PunchType::Entry => PunchType::Exit,
+            PunchType::Exit => PunchType::Entry,
+        }
+    }
+
+    /// State the employee must be in for this punch to be in sequence.
+    fn expected_state(self) -> PresenceState {
+        match self {
+            PunchType::Entry => PresenceState::Absent,
+            PunchType::Exit => PresenceState::Present,
+        }
+    }
+
+    fn resulting_state(self) -> PresenceState {
+        match self {
+            PunchType::Entry => PresenceState::Present,
+            PunchType::Exit => PresenceState::Absent,
+        }
+    }
+}
+
+/// Write path that produced an attendance row.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordSource {
+    /// Punched at the kiosk.
+    Kiosk,
+    /// Entered by an admin.
+    Manual,
+    /// Loaded from a file.
+    Import,
+    /// Generated by the system to close a gap in the sequence.
+    AutoClose,
+    /// Received from an external integration.
+    Api,
+}
+
+impl RecordSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RecordSource::Kiosk => "kiosk",
+            RecordSource::Manual => "manual",
+            RecordSource::Import => "import",
+            RecordSource::AutoClose => "auto_close",
+            RecordSource::Api => "api",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "kiosk" => Some(RecordSource::Kiosk),
+            "manual" => Some(RecordSource::Manual),
+            "import" => Some(RecordSource::Import),
+            "auto_close" => Some(RecordSource::AutoClose),
+            "api" => Some(RecordSource::Api),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        i18n::translate(match self {
+            RecordSource::Kiosk => "Kiosco",
+            RecordSource::Manual => "Manual",
+            RecordSource::Import => "Importación",
+            RecordSource::AutoClose => "Cierre automático",
+            RecordSource::Api => "API",
+        })
+    }
+}
+
+/// Identifier of this station, stored with every row it writes. Defaults to
+/// the computer name until one is configured.
+pub fn device_id(conn: &Connection) -> rusqlite::Result<String> {
+    if let Some(id) = config::get(conn, DEVICE_ID_KEY)? {
+        return Ok(id);
+    }
+    Ok(std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "kiosk".to_string()))
+}
+
+pub fn set_device_id(conn: &Connection, value: &str) -> Result<(), AppError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(AppError::validation(tr!("El identificador del dispositivo no puede estar vacío")));
+    }
+    config::set(conn, DEVICE_ID_KEY, value).map_err(AppError::from)
+}
+
+/// What to do with a punch that does not match the employee's current state.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SequencePolicy {
+    /// Refuse the punch.
+    Reject,
+    /// Record the punch, flagged as out of sequence.
+    Warn,
+    /// Insert a flagged placeholder for the missing punch, then record it.
+    Placeholder,
+}
+
+impl SequencePolicy {
+    pub fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let value = config::get(conn, POLICY_KEY)?;
+        Ok(match value.as_deref() {
+            Some("warn") => SequencePolicy::Warn,
+            Some("placeholder") => SequencePolicy::Placeholder,
+            _ => SequencePolicy::Reject,
+        })
+    }
+
+    pub fn save(self, conn: &Connection) -> rusqlite::Result<()> {
+        let value = match self {
+            SequencePolicy::Reject => "reject",
+            SequencePolicy::Warn => "warn",
+            SequencePolicy::Placeholder => "placeholder",
+        };
+        config::set(conn, POLICY_KEY, value)
+    }
+}
+
+#[derive(Serialize)]
+pub struct PunchOutcome {
+    pub message: String,
+    pub warning: Option<String>,
+    pub state: PresenceState,
+}
+
+/// An `AppError` plus, for out-of-sequence punches, the state the employee is
+/// actually in so the kiosk can offer the other punch.
+#[derive(Serialize)]
+pub struct PunchError {
+    #[serde(flatten)]
+    pub error: AppError,
+    pub current_state: Option<PresenceState>,
+}
+
+impl PunchError {
+    fn out_of_sequence(state: PresenceState) -> Self {
+        PunchError {
+            error: AppError::conflict(sequence_message(state)),
+            current_state: Some(state),
+        }
+    }
+}
+
+impl From<AppError> for PunchError {
+    fn from(error: AppError) -> Self {
+        PunchError {
+            error,
+            current_state: None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for PunchError {
+    fn from(e: rusqlite::Error) -> Self {
+        PunchError {
+            error: e.into(),
+            current_state: None,
+        }
+    }
+}
+
+fn sequence_message(state: PresenceState) -> &'static str {
+    i18n::translate(match state {
+        PresenceState::Present => "El empleado ya tiene una entrada sin salida registrada",
+        PresenceState::Absent => "El empleado no tiene una entrada previa registrada",
+    })
+}
+
+pub fn current_state(conn: &Connection, employee_id: &str) -> rusqlite::Result<PresenceState> {
+    let last_type: Option<String> = conn
+        .query_row(
+            "SELECT type FROM attendance WHERE employee_id = ?1 AND deleted_at IS NULL
+             ORDER BY timestamp DESC, id DESC LIMIT 1",
+            params![employee_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(match last_type.as_deref() {
+        Some("entry") => PresenceState::Present,
+        _ => PresenceState::Absent,
+    })
+}
+
+fn insert_punch(
+    conn: &Connection,
+    employee_id: &str,
+    employee_name: &str,
+    punch: PunchType,
+    flag: Option<&str>,
+    notes: Option<&str>,
+    source: RecordSource,
+) -> rusqlite::Result<()> {
+    let device = device_id(conn)?;
+    conn.execute(
+        "INSERT INTO attendance (employee_id, employee_name, type, flag, notes, source, device_id, timestamp, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'), datetime('now'), datetime('now'))",
+        params![employee_id, employee_name, punch.as_str(), flag, notes, source.as_str(), device],
+    )?;
+    Ok(())
+}
+
+pub fn record_punch(
+    conn: &Connection,
+    employee_id: &str,
+    employee_name: &str,
+    punch: PunchType,
+    clock_jump: Option<&ClockJump>,
+) -> Result<PunchOutcome, PunchError> {
+    let state = current_state(conn, employee_id)?;
+    let mut flag = None;
+    let mut warning = None;
+    let anomaly_id = clock_jump
+        .map(|jump| clock::log(conn, jump, employee_id))
+        .transpose()?;
+
+    let tx = conn.unchecked_transaction()?;
+
+    if state != punch.expected_state() {
+        match SequencePolicy::load(conn)? {
+            SequencePolicy::Reject => return Err(PunchError::out_of_sequence(state)),
+            SequencePolicy::Warn => {
+                flag = Some(FLAG_OUT_OF_SEQUENCE);
+                warning = Some(sequence_message(state).to_string());
+            }
+            SequencePolicy::Placeholder => {
+                let missing = punch.opposite();
+                let note = match missing {
+                    PunchType::Entry => tr!("Entrada faltante generada automáticamente"),
+                    PunchType::Exit => tr!("Salida faltante generada automáticamente"),
+                };
+                insert_punch(
+                    &tx,
+                    employee_id,
+                    employee_name,
+                    missing,
+                    Some(FLAG_PLACEHOLDER),
+                    Some(&note),
+                    RecordSource::AutoClose,
+                )?;
+                warning = Some(tr!("{}. Se generó un registro pendiente de revisión", sequence_message(state)));
+            }
+        }
+    }
+
+    // A tampered clock outranks being out of sequence: the sequence can still
+    // be checked against the rows around it, the time cannot
+    if clock_jump.is_some() {
+        flag = Some(FLAG_CLOCK_ANOMALY);
+    }
+    insert_punch(&tx, employee_id, employee_name, punch, flag, None, RecordSource::Kiosk)?;
+    if let Some(anomaly_id) = anomaly_id {
+        clock::link(&tx, anomaly_id, tx.last_insert_rowid())?;
+    }
+    tx.commit()?;
+
+    let message = match punch {
+        PunchType::Entry => tr!("Entrada registrada"),
+        PunchType::Exit => tr!("Salida registrada"),
+    };
+
+    Ok(PunchOutcome {
+        message,
+        warning,
+        state: punch.resulting_state(),
+    })
+}
+
+/// Type of the nearest live punch at or before `timestamp`, or strictly after
+/// it, other than the `exclude` row. Ties sort like `current_state`: a new row
+/// goes after existing ones.
+fn neighbour(
+    conn: &Connection,
+    employee_id: &str,
+    timestamp: &str,
+    before: bool,
+    exclude: Option<i64>,
+) -> rusqlite::Result<Option<PunchType>> {
+    let sql = if before {
+        "SELECT type FROM attendance
+         WHERE employee_id = ?1 AND deleted_at IS NULL AND datetime(timestamp) <= datetime(?2)
+               AND id IS NOT ?3
+         ORDER BY timestamp DESC, id DESC LIMIT 1"
+    } else {
+        "SELECT type FROM attendance
+         WHERE employee_id = ?1 AND deleted_at IS NULL AND datetime(timestamp) > datetime(?2)
+               AND id IS NOT ?3
+         ORDER BY timestamp ASC, id ASC LIMIT 1"
+    };
+    let value: Option<String> = conn
+        .query_row(sql, params![employee_id, timestamp, exclude], |row| row.get(0))
+        .optional()?;
+    Ok(value.as_deref().and_then(PunchType::parse))
+}
+
+/// Checks that a `punch` at `timestamp` fits between the employee's punches
+/// around it, leaving out the `exclude` row.
+fn check_fits(
+    conn: &Connection,
+    employee_id: &str,
+    punch: PunchType,
+    timestamp: &str,
+    exclude: Option<i64>,
+) -> Result<(), PunchError> {
+    let previous = neighbour(conn, employee_id, timestamp, true, exclude)?;
+    let state_before = match previous {
+        Some(PunchType::Entry) => PresenceState::Present,
+        _ => PresenceState::Absent,
+    };
+    if state_before != punch.expected_state() {
+        return Err(PunchError::out_of_sequence(state_before));
+    }
+
+    if neighbour(conn, employee_id, timestamp, false, exclude)? == Some(punch) {
+        return Err(PunchError {
+            error: AppError::conflict(match punch {
+                PunchType::Entry => tr!("El siguiente registro del empleado también es una entrada"),
+                PunchType::Exit => tr!("El siguiente registro del empleado también es una salida"),
+            }),
+            current_state: None,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that record `id`, changed to a `punch` at `timestamp` (UTC), still
+/// fits the sequence, with the same rules as `record_manual_punch`.
+pub fn check_edit(
+    conn: &Connection,
+    id: i64,
+    employee_id: &str,
+    punch: PunchType,
+    timestamp: &str,
+) -> Result<(), PunchError> {
+    check_fits(conn, employee_id, punch, timestamp, Some(id))
+}
+
+/// Inserts a punch entered by an admin at an arbitrary past `timestamp`
+/// (UTC, "YYYY-MM-DD HH:MM:SS"). The punch must fit the sequence on both
+/// sides: the punch before it and the one after it must be of the opposite
+/// type, and an exit needs an entry before it. The sequence policy does not
+/// apply here; manual punches are always rejected when they do not fit.
+pub fn record_manual_punch(
+    conn: &Connection,
+    employee_id: &str,
+    employee_name: &str,
+    punch: PunchType,
+    timestamp: &str,
+    notes: Option<&str>,
+) -> Result<i64, PunchError> {
+    check_fits(conn, employee_id, punch, timestamp, None)?;
+
+    conn.execute(
+        "INSERT INTO attendance (employee_id, employee_name, type, notes, source, device_id, timestamp, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'), datetime('now'))",
+        params![
+            employee_id,
+            employee_name,
+            punch.as_str(),
+            notes,
+            RecordSource::Manual.as_str(),
+            device_id(conn)?,
+            timestamp
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    /// Type and flag of every row, oldest first.
+    fn punches(conn: &Connection) -> Vec<(String, Option<String>)> {
+        let mut stmt = conn.prepare("SELECT type, flag FROM attendance ORDER BY id").unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    fn punch(conn: &Connection, punch: PunchType) -> Result<PunchOutcome, PunchError> {
+        record_punch(conn, "001", "Ana", punch, None)
+    }
+
+    fn manual(conn: &Connection, punch: PunchType, timestamp: &str) -> Result<i64, PunchError> {
+        record_manual_punch(conn, "001", "Ana", punch, timestamp, None)
+    }
+
+    #[test]
+    fn entry_then_exit_follows_the_sequence() {
+        let conn = migrations::test_db();
+        assert!(punch(&conn, PunchType::Entry).is_ok());
+        assert!(current_state(&conn, "001").unwrap() == PresenceState::Present);
+        assert!(punch(&conn, PunchType::Exit).is_ok());
+        assert!(current_state(&conn, "001").unwrap() == PresenceState::Absent);
+        assert_eq!(punches(&conn).len(), 2);
+    }
+
+    #[test]
+    fn reject_refuses_an_exit_without_entry() {
+        let conn = migrations::test_db();
+        let Err(e) = punch(&conn, PunchType::Exit) else {
+            panic!("exit without entry was accepted");
+        };
+        assert_eq!(e.error.code(), "CONFLICT");
+        assert!(e.current_state == Some(PresenceState::Absent));
+        assert!(punches(&conn).is_empty());
+    }
+
+    #[test]
+    fn warn_records_the_punch_flagged() {
+        let conn = migrations::test_db();
+        SequencePolicy::Warn.save(&conn).unwrap();
+        punch(&conn, PunchType::Entry).ok().unwrap();
+        let outcome = punch(&conn, PunchType::Entry).ok().unwrap();
+        assert!(outcome.warning.is_some());
+        assert_eq!(
+            punches(&conn),
+            [
+                ("entry".to_string(), None),
+                ("entry".to_string(), Some(FLAG_OUT_OF_SEQUENCE.to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholder_inserts_the_missing_punch_first() {
+        let conn = migrations::test_db();
+        SequencePolicy::Placeholder.save(&conn).unwrap();
+        punch(&conn, PunchType::Exit).ok().unwrap();
+        assert_eq!(
+            punches(&conn),
+            [
+                ("entry".to_string(), Some(FLAG_PLACEHOLDER.to_string())),
+                ("exit".to_string(), None),
+            ]
+        );
+        let source: String = conn
+            .query_row("SELECT source FROM attendance ORDER BY id LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(source, RecordSource::AutoClose.as_str());
+    }
+
+    #[test]
+    fn manual_punch_must_fit_between_its_neighbours() {
+        let conn = migrations::test_db();
+        manual(&conn, PunchType::Entry, "2024-03-04 14:00:00").ok().unwrap();
+        manual(&conn, PunchType::Exit, "2024-03-04 23:00:00").ok().unwrap();
+
+        // Another entry while already in
+        assert!(manual(&conn, PunchType::Entry, "2024-03-04 18:00:00").is_err());
+        // An exit followed by the existing exit
+        assert!(manual(&conn, PunchType::Exit, "2024-03-04 18:00:00").is_err());
+        // An entry right before another entry
+        assert!(manual(&conn, PunchType::Entry, "2024-03-03 14:00:00").is_err());
+        // The next shift fits
+        assert!(manual(&conn, PunchType::Entry, "2024-03-05 14:00:00").is_ok());
+        assert!(manual(&conn, PunchType::Exit, "2024-03-05 23:00:00").is_ok());
+    }
+
+    #[test]
+    fn edits_are_checked_without_the_edited_row() {
+        let conn = migrations::test_db();
+        manual(&conn, PunchType::Entry, "2024-03-04 14:00:00").ok().unwrap();
+        let exit = manual(&conn, PunchType::Exit, "2024-03-04 23:00:00").ok().unwrap();
+
+        assert!(check_edit(&conn, exit, "001", PunchType::Exit, "2024-03-05 01:00:00").is_ok());
+        assert!(check_edit(&conn, exit, "001", PunchType::Entry, "2024-03-04 23:00:00").is_err());
+        assert!(check_edit(&conn, exit, "001", PunchType::Exit, "2024-03-04 13:00:00").is_err());
+    }
+}
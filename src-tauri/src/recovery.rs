@@ -0,0 +1,297 @@
+// Startup integrity check and recovery mode. When the database fails
+// `PRAGMA integrity_check` or `PRAGMA foreign_key_check`, or cannot be opened
+// or migrated at all, the app starts without it and the frontend shows a
+// recovery screen instead of the kiosk. From there an admin can see what went
+// wrong, restore the latest backup, or export whatever rows are still
+// readable.
+//
+// An encrypted database whose secret is not known at startup is not checked
+// until an admin unlocks it.
+//
+// Recovery actions take an admin login, or the recovery secret kept in
+// settings.json when the accounts in the database cannot be trusted.
+
+use crate::auth;
+use crate::backup;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::storage;
+use crate::timezone;
+use crate::users::Permission;
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use rust_xlsxwriter::{Format, Workbook};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How far startup got.
+pub enum Startup {
+    /// The database is open and the app runs normally.
+    Ready,
+    /// The database is encrypted and waits for its secret.
+    Locked,
+    /// The database failed to open, check or migrate.
+    Failed(Vec<String>),
+}
+
+pub struct Recovery {
+    /// `None` when settings.json could not be read to find the database.
+    pub db_path: Option<PathBuf>,
+    /// Directory of settings.json.
+    pub config_dir: PathBuf,
+    pub startup: Mutex<Startup>,
+    /// Secret of an encrypted database, once known.
+    pub key: Mutex<Option<String>>,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryStatus {
+    pub active: bool,
+    pub locked: bool,
+    pub problems: Vec<String>,
+    pub db_path: String,
+    pub latest_backup: Option<String>,
+    pub has_recovery_secret: bool,
+    /// Why the last startup or daily backup failed, when the app is running.
+    pub backup_error: Option<String>,
+}
+
+impl Recovery {
+    pub fn status(&self) -> RecoveryStatus {
+        let key = self.key();
+        let startup = self.startup.lock().unwrap();
+        let problems = match &*startup {
+            Startup::Failed(problems) => problems.clone(),
+            _ => Vec::new(),
+        };
+        RecoveryStatus {
+            active: !problems.is_empty(),
+            locked: matches!(*startup, Startup::Locked),
+            problems,
+            db_path: self.db_path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+            latest_backup: self
+                .db_path
+                .as_deref()
+                .and_then(|db_path| latest_backup(db_path, key.as_deref()))
+                .map(|p| p.display().to_string()),
+            has_recovery_secret: matches!(storage::recovery_secret_hash(&self.config_dir), Ok(Some(_))),
+            backup_error: None,
+        }
+    }
+
+    pub fn db_path(&self) -> Result<&Path, AppError> {
+        self.db_path
+            .as_deref()
+            .ok_or_else(|| AppError::conflict(tr!("No se pudo determinar la ubicación de la base de datos")))
+    }
+
+    pub fn key(&self) -> Option<String> {
+        self.key.lock().unwrap().clone()
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(*self.startup.lock().unwrap(), Startup::Failed(_))
+    }
+}
+
+/// Runs the integrity and foreign key checks. Failing to run them at all, as
+/// with a file that is not a database, counts as a problem too.
+pub fn check(conn: &Connection) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    match pragma_rows(conn, "PRAGMA integrity_check", |row| row.get::<_, String>(0)) {
+        Ok(rows) => problems.extend(rows.into_iter().filter(|r| r != "ok")),
+        Err(e) => problems.push(e.to_string()),
+    }
+
+    let foreign_keys = pragma_rows(conn, "PRAGMA foreign_key_check", |row| {
+        Ok(tr!(
+            "Referencia inválida en {} (fila {}) hacia {}",
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<i64>>(1)?.map_or("?".to_string(), |id| id.to_string()),
+            row.get::<_, String>(2)?,
+        ))
+    });
+    match foreign_keys {
+        Ok(rows) => problems.extend(rows),
+        Err(e) => problems.push(e.to_string()),
+    }
+
+    problems
+}
+
+fn pragma_rows<T>(
+    conn: &Connection,
+    sql: &str,
+    f: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> rusqlite::Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], f)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Recovery actions need an account allowed to manage settings. Without a
+/// username the password is checked against the recovery secret or the
+/// database's encryption secret instead, neither of which is read from the
+/// damaged database. With none of them to check against the actions are
+/// refused.
+pub fn authorize(recovery: &Recovery, username: &str, password: &str) -> Result<(), AppError> {
+    let key = recovery.key();
+    if username.trim().is_empty() {
+        if key.as_deref() == Some(password) {
+            return Ok(());
+        }
+        if let Some(hash) = storage::recovery_secret_hash(&recovery.config_dir)? {
+            if auth::verify_password(password, &hash) {
+                return Ok(());
+            }
+        }
+        return Err(AppError::auth(tr!("Clave de recuperación incorrecta")));
+    }
+
+    let conn = storage::open_read_only(recovery.db_path()?, key.as_deref()).map_err(|_| {
+        AppError::auth(tr!(
+            "No se pueden leer las cuentas, deja el usuario vacío e ingresa la clave de recuperación"
+        ))
+    })?;
+    let user = auth::login(&conn, username, password)?;
+    if !user.role.allows(Permission::ManageSettings) {
+        return Err(AppError::auth(tr!("No tienes permiso para realizar esta acción")));
+    }
+    Ok(())
+}
+
+/// Sets the secret that authorizes recovery actions.
+pub fn set_secret(config_dir: &Path, secret: &str) -> Result<(), AppError> {
+    storage::validate_secret(secret)?;
+    storage::set_recovery_secret_hash(config_dir, &auth::hash_password(secret)?)
+}
+
+pub fn latest_backup(db_path: &Path, key: Option<&str>) -> Option<PathBuf> {
+    backup::list_in(&backup::dir_for(db_path, key), db_path)
+        .into_iter()
+        .next()
+        .map(|b| PathBuf::from(b.path))
+}
+
+/// Replaces the database with the latest backup. The damaged file is kept as
+/// `<db name>.damaged-<timestamp>`, and a backup that fails validation is
+/// refused.
+pub fn restore_latest_backup(db_path: &Path, key: Option<&str>) -> Result<PathBuf, AppError> {
+    let backup = latest_backup(db_path, key)
+        .ok_or_else(|| AppError::not_found(tr!("No hay respaldos disponibles")))?;
+    backup::validate(&backup, key)?;
+
+    if db_path.exists() {
+        let name = db_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let stamp = timezone::now_local().format("%Y%m%d-%H%M%S");
+        let damaged = db_path.with_file_name(format!("{}.damaged-{}", name, stamp));
+        std::fs::rename(db_path, &damaged)
+            .map_err(|e| AppError::database(tr!("No se pudo apartar la base de datos dañada"), e))?;
+    }
+    std::fs::copy(&backup, db_path).map_err(|e| AppError::database(tr!("No se pudo restaurar el respaldo"), e))?;
+    Ok(backup)
+}
+
+fn table_names(conn: &Connection) -> Vec<String> {
+    let names = pragma_rows(
+        conn,
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        |row| row.get::<_, String>(0),
+    );
+    // With a damaged catalog, fall back to the tables this app creates
+    names.unwrap_or_else(|_| {
+        [
+            "attendance", "audit_log", "badges", "clock_anomalies", "config", "employees",
+            "holidays", "schedule_days", "schedules", "users",
+        ]
+        .iter()
+        .map(|t| t.to_string())
+        .collect()
+    })
+}
+
+/// Columns holding credentials, which the salvage export leaves out.
+const SECRET_COLUMNS: [(&str, &str); 2] = [("users", "password_hash"), ("employees", "pin_hash")];
+
+fn is_secret_column(table: &str, column: &str) -> bool {
+    SECRET_COLUMNS
+        .iter()
+        .any(|&(t, c)| t.eq_ignore_ascii_case(table) && c.eq_ignore_ascii_case(column))
+}
+
+/// Whether a row of `table` is a `config` entry holding a password.
+fn is_secret_row(table: &str, columns: &[String], row: &rusqlite::Row) -> bool {
+    if !table.eq_ignore_ascii_case("config") {
+        return false;
+    }
+    let Some(key_col) = columns.iter().position(|c| c.eq_ignore_ascii_case("key")) else {
+        return false;
+    };
+    match row.get_ref(key_col) {
+        Ok(value) => auth::LEGACY_KEYS.contains(&cell_text(value).as_str()),
+        // An unreadable key could be anything
+        Err(_) => true,
+    }
+}
+
+fn cell_text(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+/// Writes every table that can still be read to its own worksheet, stopping
+/// at the first unreadable row of each table. Password and PIN hashes are left
+/// out. Returns the number of rows written.
+pub fn export_salvage(db_path: &Path, key: Option<&str>, target: &Path) -> Result<usize, AppError> {
+    let conn = storage::open_read_only(db_path, key)?;
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    let mut total = 0;
+    let mut sheets = 0;
+
+    for table in table_names(&conn) {
+        let Ok(mut stmt) = conn.prepare(&format!("SELECT * FROM \"{}\"", table)) else {
+            continue;
+        };
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let kept: Vec<usize> = (0..columns.len())
+            .filter(|&col| !is_secret_column(&table, &columns[col]))
+            .collect();
+        let Ok(mut rows) = stmt.query([]) else {
+            continue;
+        };
+
+        let worksheet = workbook.add_worksheet();
+        sheets += 1;
+        worksheet.set_name(&table)?;
+        for (out_col, &col) in kept.iter().enumerate() {
+            worksheet.write_string_with_format(0, out_col as u16, &columns[col], &header_format)?;
+        }
+
+        let mut row_idx = 1u32;
+        while let Ok(Some(row)) = rows.next() {
+            if is_secret_row(&table, &columns, row) {
+                continue;
+            }
+            for (out_col, &col) in kept.iter().enumerate() {
+                let text = row.get_ref(col).map(cell_text).unwrap_or_default();
+                worksheet.write_string(row_idx, out_col as u16, &text)?;
+            }
+            row_idx += 1;
+            total += 1;
+        }
+    }
+
+    if sheets == 0 {
+        return Err(AppError::not_found(tr!("No se encontraron datos recuperables")));
+    }
+    workbook.save(target)?;
+    Ok(total)
+}
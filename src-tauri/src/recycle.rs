@@ -0,0 +1,46 @@
+// Recycle bin: records and employees are soft-deleted by stamping `deleted_at`
+// and `deleted_by`. Every other query filters on `deleted_at IS NULL`; rows
+// here can be restored or purged for good.
+
+use crate::{
+    row_to_employee, row_to_record, AttendanceRecord, Employee, EMPLOYEE_COLUMNS, RECORD_COLUMNS,
+};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct RecycleBin {
+    records: Vec<AttendanceRecord>,
+    employees: Vec<Employee>,
+}
+
+/// Most recently deleted first.
+pub fn list(conn: &Connection) -> rusqlite::Result<RecycleBin> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attendance WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        RECORD_COLUMNS
+    ))?;
+    let records = stmt
+        .query_map([], row_to_record)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM employees WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        EMPLOYEE_COLUMNS
+    ))?;
+    let employees = stmt
+        .query_map([], row_to_employee)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(RecycleBin { records, employees })
+}
+
+/// Attendance rows still referencing an employee, deleted ones included.
+/// An employee cannot be purged while any remain, so no record is orphaned.
+pub fn attendance_count(conn: &Connection, employee_id: &str) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM attendance WHERE employee_id = ?1",
+        params![employee_id],
+        |row| row.get(0),
+    )
+}
@@ -0,0 +1,499 @@
+// Employee work schedules and tardiness detection.
+//
+// A schedule is a weekly template that takes effect on `effective_from` and stays in
+// force until the employee's next schedule starts, so editing a schedule never
+// changes how earlier days are evaluated.
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::presence::FLAG_PLACEHOLDER;
+use crate::timesheet::parse_timestamp;
+use crate::workday::WorkdayBoundary;
+use crate::{config, AttendanceRecord};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const GRACE_KEY: &str = "tardiness_grace_minutes";
+const DEFAULT_GRACE_MINUTES: i64 = 5;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleDay {
+    /// 0 = Monday ... 6 = Sunday.
+    pub weekday: u32,
+    pub start_time: String,
+    pub end_time: String,
+}
+
+#[derive(Serialize)]
+pub struct WeeklySchedule {
+    pub employee_id: String,
+    pub effective_from: String,
+    pub days: Vec<ScheduleDay>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviationKind {
+    Late,
+    EarlyDeparture,
+}
+
+#[derive(Serialize)]
+pub struct Deviation {
+    pub record_id: i64,
+    pub employee_id: String,
+    pub employee_name: Option<String>,
+    pub workday: String,
+    pub kind: DeviationKind,
+    pub expected: String,
+    pub actual: String,
+    pub minutes: i64,
+}
+
+#[derive(Serialize)]
+pub struct TardinessSummary {
+    employee_id: String,
+    employee_name: Option<String>,
+    late_count: i64,
+    late_minutes: i64,
+    early_departure_count: i64,
+    early_departure_minutes: i64,
+    incidents: Vec<Deviation>,
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+pub fn grace_minutes(conn: &Connection) -> rusqlite::Result<i64> {
+    Ok(config::get(conn, GRACE_KEY)?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_GRACE_MINUTES))
+}
+
+pub fn set_grace_minutes(conn: &Connection, minutes: i64) -> Result<(), AppError> {
+    if !(0..=240).contains(&minutes) {
+        return Err(AppError::validation(tr!("La tolerancia debe estar entre 0 y 240 minutos")));
+    }
+    config::set(conn, GRACE_KEY, &minutes.to_string()).map_err(AppError::from)
+}
+
+pub fn get_schedules(conn: &Connection, employee_id: &str) -> rusqlite::Result<Vec<WeeklySchedule>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.effective_from, d.weekday, d.start_time, d.end_time
+         FROM schedules s LEFT JOIN schedule_days d ON d.schedule_id = s.id
+         WHERE s.employee_id = ?1
+         ORDER BY s.effective_from DESC, d.weekday",
+    )?;
+    let rows = stmt.query_map(params![employee_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<u32>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    let mut schedules: Vec<WeeklySchedule> = Vec::new();
+    for row in rows {
+        let (effective_from, weekday, start_time, end_time) = row?;
+        if schedules.last().is_none_or(|s| s.effective_from != effective_from) {
+            schedules.push(WeeklySchedule {
+                employee_id: employee_id.to_string(),
+                effective_from: effective_from.clone(),
+                days: Vec::new(),
+            });
+        }
+        if let (Some(weekday), Some(start_time), Some(end_time)) = (weekday, start_time, end_time) {
+            schedules.last_mut().unwrap().days.push(ScheduleDay {
+                weekday,
+                start_time,
+                end_time,
+            });
+        }
+    }
+
+    Ok(schedules)
+}
+
+/// Stores a weekly schedule starting on `effective_from`, replacing any schedule
+/// that starts on that same date. An empty `days` list means no working days.
+pub fn set_schedule(
+    conn: &Connection,
+    employee_id: &str,
+    effective_from: &str,
+    days: &[ScheduleDay],
+) -> Result<(), AppError> {
+    if NaiveDate::parse_from_str(effective_from, "%Y-%m-%d").is_err() {
+        return Err(AppError::validation(tr!("Fecha de vigencia inválida")));
+    }
+    let mut seen = [false; 7];
+    for day in days {
+        if day.weekday > 6 || seen[day.weekday as usize] {
+            return Err(AppError::validation(tr!("Día de la semana inválido o repetido")));
+        }
+        seen[day.weekday as usize] = true;
+        if parse_time(&day.start_time).is_none() || parse_time(&day.end_time).is_none() {
+            return Err(AppError::validation(tr!("Formato de hora inválido, se esperaba HH:MM")));
+        }
+    }
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM employees WHERE id = ?1 AND deleted_at IS NULL)",
+            params![employee_id],
+            |row| row.get(0),
+        )?;
+    if !exists {
+        return Err(AppError::not_found(tr!("Empleado no encontrado")));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM schedule_days WHERE schedule_id IN
+         (SELECT id FROM schedules WHERE employee_id = ?1 AND effective_from = ?2)",
+        params![employee_id, effective_from],
+    )?;
+    tx.execute(
+        "DELETE FROM schedules WHERE employee_id = ?1 AND effective_from = ?2",
+        params![employee_id, effective_from],
+    )?;
+    tx.execute(
+        "INSERT INTO schedules (employee_id, effective_from) VALUES (?1, ?2)",
+        params![employee_id, effective_from],
+    )?;
+    let schedule_id = tx.last_insert_rowid();
+    for day in days {
+        tx.execute(
+            "INSERT INTO schedule_days (schedule_id, weekday, start_time, end_time) VALUES (?1, ?2, ?3, ?4)",
+            params![schedule_id, day.weekday, day.start_time, day.end_time],
+        )?;
+    }
+    tx.commit().map_err(AppError::from)
+}
+
+pub fn delete_schedule(conn: &Connection, employee_id: &str, effective_from: &str) -> Result<(), AppError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM schedule_days WHERE schedule_id IN
+         (SELECT id FROM schedules WHERE employee_id = ?1 AND effective_from = ?2)",
+        params![employee_id, effective_from],
+    )?;
+    let rows_affected = tx
+        .execute(
+            "DELETE FROM schedules WHERE employee_id = ?1 AND effective_from = ?2",
+            params![employee_id, effective_from],
+        )?;
+    if rows_affected == 0 {
+        return Err(AppError::not_found(tr!("Horario no encontrado")));
+    }
+    tx.commit().map_err(AppError::from)
+}
+
+/// Expected start and end of a scheduled workday. The end falls on the next
+/// calendar day when it is not after the start (night shifts).
+#[derive(Clone, Copy)]
+pub struct ExpectedShift {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+type WeekTemplate = [Option<(NaiveTime, NaiveTime)>; 7];
+
+/// All schedule versions of the loaded employees, for in-memory lookups.
+pub struct ScheduleBook {
+    versions: HashMap<String, Vec<(NaiveDate, WeekTemplate)>>,
+}
+
+impl ScheduleBook {
+    pub fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let mut stmt = conn.prepare(
+            "SELECT s.employee_id, s.effective_from, d.weekday, d.start_time, d.end_time
+             FROM schedules s LEFT JOIN schedule_days d ON d.schedule_id = s.id
+             ORDER BY s.employee_id, s.effective_from",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<u32>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+
+        let mut versions: HashMap<String, Vec<(NaiveDate, WeekTemplate)>> = HashMap::new();
+        for row in rows {
+            let (employee_id, effective_from, weekday, start_time, end_time) = row?;
+            let Ok(from) = NaiveDate::parse_from_str(&effective_from, "%Y-%m-%d") else {
+                continue;
+            };
+            let list = versions.entry(employee_id).or_default();
+            if list.last().is_none_or(|(d, _)| *d != from) {
+                list.push((from, [None; 7]));
+            }
+            if let (Some(weekday), Some(start), Some(end)) = (
+                weekday.filter(|w| *w < 7),
+                start_time.as_deref().and_then(parse_time),
+                end_time.as_deref().and_then(parse_time),
+            ) {
+                list.last_mut().unwrap().1[weekday as usize] = Some((start, end));
+            }
+        }
+
+        Ok(ScheduleBook { versions })
+    }
+
+    pub fn expected(&self, employee_id: &str, day: NaiveDate) -> Option<ExpectedShift> {
+        let (_, week) = self
+            .versions
+            .get(employee_id)?
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= day)?;
+        let (start, end) = week[day.weekday().num_days_from_monday() as usize]?;
+        let start = day.and_time(start);
+        let mut end = day.and_time(end);
+        if end <= start {
+            end += Duration::days(1);
+        }
+        Some(ExpectedShift { start, end })
+    }
+}
+
+/// Compares each workday's first entry and last exit against the schedule.
+/// An exit belongs to the workday of the entry it closes. Placeholders carry the
+/// time they were generated, not a time anyone punched, so they are left out.
+/// Only deviations beyond the grace period are returned, with their full length
+/// in minutes.
+pub fn deviations(
+    records: &[AttendanceRecord],
+    book: &ScheduleBook,
+    boundary: &WorkdayBoundary,
+    grace_minutes: i64,
+) -> Vec<Deviation> {
+    let mut sorted: Vec<&AttendanceRecord> = records.iter().collect();
+    sorted.sort_by(|a, b| {
+        (&a.employee_id, a.instant, a.id).cmp(&(&b.employee_id, b.instant, b.id))
+    });
+
+    // (employee, workday) -> (first entry, last exit)
+    type Bounds<'a> = (
+        Option<(&'a AttendanceRecord, NaiveDateTime)>,
+        Option<(&'a AttendanceRecord, NaiveDateTime)>,
+    );
+    let mut days: BTreeMap<(&str, NaiveDate), Bounds> = BTreeMap::new();
+    let mut previous: Option<(&AttendanceRecord, NaiveDate)> = None;
+
+    for record in sorted {
+        let Some(ts) = parse_timestamp(&record.timestamp) else {
+            continue;
+        };
+        let day = match (record.r#type.as_str(), previous) {
+            ("exit", Some((prev, prev_day)))
+                if prev.r#type == "entry" && prev.employee_id == record.employee_id =>
+            {
+                prev_day
+            }
+            _ => boundary.business_date(ts),
+        };
+        previous = Some((record, day));
+        if record.flag.as_deref() == Some(FLAG_PLACEHOLDER) {
+            continue;
+        }
+
+        let bounds = days.entry((record.employee_id.as_str(), day)).or_default();
+        if record.r#type == "entry" {
+            if bounds.0.is_none() {
+                bounds.0 = Some((record, ts));
+            }
+        } else {
+            bounds.1 = Some((record, ts));
+        }
+    }
+
+    let mut result = Vec::new();
+    for ((employee_id, day), (first_entry, last_exit)) in days {
+        let Some(expected) = book.expected(employee_id, day) else {
+            continue;
+        };
+        let workday = day.format("%Y-%m-%d").to_string();
+
+        if let Some((record, ts)) = first_entry {
+            let minutes = (ts - expected.start).num_minutes();
+            if minutes > grace_minutes {
+                result.push(Deviation {
+                    record_id: record.id,
+                    employee_id: employee_id.to_string(),
+                    employee_name: record.employee_name.clone(),
+                    workday: workday.clone(),
+                    kind: DeviationKind::Late,
+                    expected: expected.start.format("%Y-%m-%d %H:%M").to_string(),
+                    actual: record.timestamp.clone(),
+                    minutes,
+                });
+            }
+        }
+        if let Some((record, ts)) = last_exit {
+            let minutes = (expected.end - ts).num_minutes();
+            if minutes > grace_minutes {
+                result.push(Deviation {
+                    record_id: record.id,
+                    employee_id: employee_id.to_string(),
+                    employee_name: record.employee_name.clone(),
+                    workday,
+                    kind: DeviationKind::EarlyDeparture,
+                    expected: expected.end.format("%Y-%m-%d %H:%M").to_string(),
+                    actual: record.timestamp.clone(),
+                    minutes,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+pub fn summarize(deviations: Vec<Deviation>) -> Vec<TardinessSummary> {
+    let mut by_employee: BTreeMap<String, TardinessSummary> = BTreeMap::new();
+    for deviation in deviations {
+        let summary = by_employee
+            .entry(deviation.employee_id.clone())
+            .or_insert_with(|| TardinessSummary {
+                employee_id: deviation.employee_id.clone(),
+                employee_name: deviation.employee_name.clone(),
+                late_count: 0,
+                late_minutes: 0,
+                early_departure_count: 0,
+                early_departure_minutes: 0,
+                incidents: Vec::new(),
+            });
+        match deviation.kind {
+            DeviationKind::Late => {
+                summary.late_count += 1;
+                summary.late_minutes += deviation.minutes;
+            }
+            DeviationKind::EarlyDeparture => {
+                summary.early_departure_count += 1;
+                summary.early_departure_minutes += deviation.minutes;
+            }
+        }
+        summary.incidents.push(deviation);
+    }
+    by_employee.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+    use crate::timesheet::test_record;
+
+    fn weekdays(start_time: &str, end_time: &str) -> Vec<ScheduleDay> {
+        (0..5)
+            .map(|weekday| ScheduleDay {
+                weekday,
+                start_time: start_time.to_string(),
+                end_time: end_time.to_string(),
+            })
+            .collect()
+    }
+
+    /// Schedule book for employee 001 working 09:00-17:00 on weekdays.
+    fn office_hours() -> ScheduleBook {
+        let conn = migrations::test_db();
+        conn.execute("INSERT INTO employees (id, name) VALUES ('001', 'Ana')", []).unwrap();
+        set_schedule(&conn, "001", "2024-01-01", &weekdays("09:00", "17:00")).unwrap();
+        ScheduleBook::load(&conn).unwrap()
+    }
+
+    fn kinds(deviations: &[Deviation]) -> Vec<(DeviationKind, i64)> {
+        deviations.iter().map(|d| (d.kind, d.minutes)).collect()
+    }
+
+    #[test]
+    fn placeholder_exit_is_not_an_early_departure() {
+        let book = office_hours();
+        let boundary = WorkdayBoundary::starting_at("00:00");
+        let entry = test_record(1, "entry", "2024-03-04 09:00:00", None);
+
+        let punched = [entry.clone(), test_record(2, "exit", "2024-03-04 10:30:00", None)];
+        let found = deviations(&punched, &book, &boundary, 5);
+        assert_eq!(kinds(&found), [(DeviationKind::EarlyDeparture, 390)]);
+
+        let placeholder = test_record(2, "exit", "2024-03-04 10:30:00", Some(FLAG_PLACEHOLDER));
+        let generated = [entry, placeholder];
+        assert!(deviations(&generated, &book, &boundary, 5).is_empty());
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn times(shift: Option<ExpectedShift>) -> Option<(String, String)> {
+        shift.map(|s| (s.start.to_string(), s.end.to_string()))
+    }
+
+    #[test]
+    fn schedule_change_leaves_earlier_days_alone() {
+        let conn = migrations::test_db();
+        conn.execute("INSERT INTO employees (id, name) VALUES ('001', 'Ana')", []).unwrap();
+        set_schedule(&conn, "001", "2024-01-01", &weekdays("09:00", "17:00")).unwrap();
+        set_schedule(&conn, "001", "2024-03-11", &weekdays("07:00", "15:00")).unwrap();
+        let book = ScheduleBook::load(&conn).unwrap();
+
+        let before = book.expected("001", date("2024-03-08"));
+        assert_eq!(times(before).unwrap().0, "2024-03-08 09:00:00");
+        let after = book.expected("001", date("2024-03-11"));
+        assert_eq!(times(after).unwrap().0, "2024-03-11 07:00:00");
+        // Before the first schedule, on weekends and for others nothing is expected
+        assert!(book.expected("001", date("2023-12-29")).is_none());
+        assert!(book.expected("001", date("2024-03-09")).is_none());
+        assert!(book.expected("002", date("2024-03-08")).is_none());
+    }
+
+    #[test]
+    fn night_shift_ends_the_next_day() {
+        let conn = migrations::test_db();
+        conn.execute("INSERT INTO employees (id, name) VALUES ('001', 'Ana')", []).unwrap();
+        set_schedule(&conn, "001", "2024-01-01", &weekdays("22:00", "06:00")).unwrap();
+        let book = ScheduleBook::load(&conn).unwrap();
+
+        let expected = times(book.expected("001", date("2024-03-04")));
+        assert_eq!(
+            expected,
+            Some(("2024-03-04 22:00:00".to_string(), "2024-03-05 06:00:00".to_string()))
+        );
+
+        // The exit after midnight closes Monday's shift, even with a midnight boundary
+        let records = [
+            test_record(1, "entry", "2024-03-04 22:00:00", None),
+            test_record(2, "exit", "2024-03-05 05:30:00", None),
+        ];
+        let found = deviations(&records, &book, &WorkdayBoundary::starting_at("00:00"), 5);
+        assert_eq!(kinds(&found), [(DeviationKind::EarlyDeparture, 30)]);
+        assert_eq!(found[0].workday, "2024-03-04");
+    }
+
+    #[test]
+    fn lateness_within_the_grace_period_is_ignored() {
+        let book = office_hours();
+        let boundary = WorkdayBoundary::starting_at("00:00");
+        let on_time = [
+            test_record(1, "entry", "2024-03-04 09:05:00", None),
+            test_record(2, "exit", "2024-03-04 16:55:00", None),
+        ];
+        assert!(deviations(&on_time, &book, &boundary, 5).is_empty());
+
+        let late = [
+            test_record(1, "entry", "2024-03-04 09:06:00", None),
+            test_record(2, "exit", "2024-03-04 16:54:00", None),
+        ];
+        assert_eq!(
+            kinds(&deviations(&late, &book, &boundary, 5)),
+            [(DeviationKind::Late, 6), (DeviationKind::EarlyDeparture, 6)]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+// Admin sessions: the login command issues a random token that every privileged
+// command must present. Tokens live only in memory and expire after a fixed
+// lifetime or a period without use, whichever comes first.
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::users::SessionUser;
+use rand::RngCore;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const SESSION_LIFETIME: Duration = Duration::from_secs(8 * 60 * 60);
+const IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+struct Session {
+    user: SessionUser,
+    created_at: Instant,
+    last_used: Instant,
+}
+
+impl Session {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.created_at) > SESSION_LIFETIME
+            || now.duration_since(self.last_used) > IDLE_TIMEOUT
+    }
+}
+
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: HashMap<String, Session>,
+}
+
+impl SessionStore {
+    pub fn issue(&mut self, user: SessionUser) -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let now = Instant::now();
+        self.sessions.retain(|_, s| !s.is_expired(now));
+        self.sessions.insert(
+            token.clone(),
+            Session {
+                user,
+                created_at: now,
+                last_used: now,
+            },
+        );
+        token
+    }
+
+    /// Checks the token, refreshes its idle timer and returns its user.
+    pub fn validate(&mut self, token: &str) -> Result<SessionUser, AppError> {
+        let now = Instant::now();
+        match self.sessions.get_mut(token) {
+            Some(session) if !session.is_expired(now) => {
+                session.last_used = now;
+                Ok(session.user.clone())
+            }
+            Some(_) => {
+                self.sessions.remove(token);
+                Err(AppError::auth(tr!("La sesión expiró, inicia sesión nuevamente")))
+            }
+            None => Err(AppError::auth(tr!("Sesión inválida, inicia sesión nuevamente"))),
+        }
+    }
+
+    pub fn revoke(&mut self, token: &str) {
+        self.sessions.remove(token);
+    }
+
+    /// Ends every session of a user, e.g. after a role or password change.
+    /// `keep` is spared so users changing their own password stay logged in.
+    pub fn revoke_user(&mut self, user_id: i64, keep: Option<&str>) {
+        self.sessions
+            .retain(|t, s| s.user.id != user_id || keep == Some(t.as_str()));
+    }
+
+    /// Ends every session, e.g. after the database was replaced by a backup.
+    pub fn revoke_all(&mut self) {
+        self.sessions.clear();
+    }
+}
@@ -0,0 +1,271 @@
+// Location of the database file. By default it lives in the OS app-data
+// directory; it can be moved with, in order of precedence:
+//
+//   --db <path>            command-line flag
+//   IMPA_ATTENDANCE_DB     environment variable
+//   database_path          key in settings.json in the app config directory
+//
+// Older builds opened `attendance.db` relative to the working directory. When
+// the resolved file does not exist yet, such a database found next to the
+// executable or in the working directory is copied over and the original is
+// renamed so it is not picked up again.
+//
+// The database can be encrypted with SQLCipher, which derives the page key
+// from an admin secret and a salt stored in the file. An encrypted database
+// stays locked at startup until the secret is entered, unless it is provided
+// through IMPA_ATTENDANCE_KEY for unattended kiosks.
+//
+// settings.json also keeps the hash of the recovery secret, which authorizes
+// recovery actions without reading the (possibly damaged) database.
+
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::migrations;
+use rusqlite::{params, Connection, DatabaseName, OpenFlags};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const DB_FILE_NAME: &str = "attendance.db";
+const ENV_VAR: &str = "IMPA_ATTENDANCE_DB";
+const KEY_ENV_VAR: &str = "IMPA_ATTENDANCE_KEY";
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+const MIN_SECRET_LENGTH: usize = 8;
+const CLI_FLAG: &str = "--db";
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Deserialize, Default)]
+struct Settings {
+    database_path: Option<PathBuf>,
+    /// Argon2 hash of the recovery secret.
+    recovery_secret_hash: Option<String>,
+}
+
+fn cli_override() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == CLI_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix("--db=") {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+fn env_override() -> Option<PathBuf> {
+    std::env::var_os(ENV_VAR)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+fn read_settings<T: DeserializeOwned + Default>(config_dir: &Path) -> Result<T, AppError> {
+    let file = config_dir.join(SETTINGS_FILE);
+    if !file.exists() {
+        return Ok(T::default());
+    }
+    let content = std::fs::read_to_string(&file)
+        .map_err(|e| AppError::database(tr!("No se pudo leer {}", file.display()), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::database(tr!("Configuración inválida en {}", file.display()), e))
+}
+
+fn settings_override(config_dir: &Path) -> Result<Option<PathBuf>, AppError> {
+    let settings: Settings = read_settings(config_dir)?;
+    // Relative paths in the file are relative to the file itself
+    Ok(settings.database_path.map(|p| config_dir.join(p)))
+}
+
+pub fn recovery_secret_hash(config_dir: &Path) -> Result<Option<String>, AppError> {
+    let settings: Settings = read_settings(config_dir)?;
+    Ok(settings.recovery_secret_hash)
+}
+
+/// Stores the recovery secret hash, keeping the other keys in the file.
+pub fn set_recovery_secret_hash(config_dir: &Path, hash: &str) -> Result<(), AppError> {
+    let mut settings: serde_json::Map<String, serde_json::Value> = read_settings(config_dir)?;
+    settings.insert("recovery_secret_hash".to_string(), hash.into());
+
+    let file = config_dir.join(SETTINGS_FILE);
+    std::fs::create_dir_all(config_dir).map_err(|e| {
+        AppError::database(tr!("No se pudo crear el directorio {}", config_dir.display()), e)
+    })?;
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| AppError::database(tr!("No se pudo guardar {}", file.display()), e))?;
+    std::fs::write(&file, content)
+        .map_err(|e| AppError::database(tr!("No se pudo guardar {}", file.display()), e))
+}
+
+/// Where the database should be opened from.
+pub fn resolve_path(data_dir: &Path, config_dir: &Path) -> Result<PathBuf, AppError> {
+    if let Some(path) = cli_override().or_else(env_override) {
+        return Ok(path);
+    }
+    if let Some(path) = settings_override(config_dir)? {
+        return Ok(path);
+    }
+    Ok(data_dir.join(DB_FILE_NAME))
+}
+
+fn legacy_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(Path::to_path_buf)) {
+        candidates.push(exe_dir.join(DB_FILE_NAME));
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd.join(DB_FILE_NAME));
+    }
+    candidates
+}
+
+/// Copies a database left by an older build to `target`. Empty files, which
+/// older builds created when launched from the wrong directory, are ignored.
+fn migrate_legacy(target: &Path) -> Result<(), AppError> {
+    let legacy = legacy_candidates().into_iter().find(|p| {
+        p.as_path() != target && std::fs::metadata(p).map(|m| m.len() > 0).unwrap_or(false)
+    });
+    let Some(legacy) = legacy else {
+        return Ok(());
+    };
+
+    let conn = Connection::open(&legacy)?;
+    conn.execute("VACUUM INTO ?1", [target.to_string_lossy().as_ref()])
+        .map_err(|e| {
+            AppError::database(tr!("No se pudo migrar la base de datos {}", legacy.display()), e)
+        })?;
+    drop(conn);
+
+    // The copy is already in place, so a read-only install directory only
+    // means the old file stays where it was.
+    let _ = std::fs::rename(&legacy, legacy.with_extension("db.migrated"));
+    Ok(())
+}
+
+/// Opens the database at `path`, creating its directory and bringing over a
+/// legacy database on first run.
+pub fn open(path: &Path, key: Option<&str>) -> Result<Connection, AppError> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AppError::database(tr!("No se pudo crear el directorio {}", dir.display()), e))?;
+    }
+    if !path.exists() {
+        migrate_legacy(path)?;
+    }
+    let conn = Connection::open(path)
+        .map_err(|e| AppError::database(tr!("No se pudo abrir {}", path.display()), e))?;
+    if let Some(key) = key {
+        apply_key(&conn, key)?;
+    }
+    Ok(conn)
+}
+
+pub fn open_read_only(path: &Path, key: Option<&str>) -> Result<Connection, AppError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| AppError::database(tr!("No se pudo abrir {}", path.display()), e))?;
+    if let Some(key) = key {
+        apply_key(&conn, key)?;
+    }
+    Ok(conn)
+}
+
+fn apply_key(conn: &Connection, key: &str) -> Result<(), AppError> {
+    conn.pragma_update(None, "key", key)?;
+    // SQLCipher only checks the key on the first read
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+        .map_err(|_| AppError::auth(tr!("Clave de cifrado incorrecta")))
+}
+
+/// Whether the file is encrypted. Plain SQLite files start with a fixed
+/// header, SQLCipher files with their random salt.
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// Secret from the environment, for kiosks that must start unattended.
+pub fn env_key() -> Option<String> {
+    std::env::var(KEY_ENV_VAR).ok().filter(|k| !k.is_empty())
+}
+
+pub fn validate_secret(secret: &str) -> Result<(), AppError> {
+    if secret.chars().count() < MIN_SECRET_LENGTH {
+        return Err(AppError::validation(tr!(
+            "La clave de cifrado debe tener al menos {} caracteres",
+            MIN_SECRET_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Copies the database behind `conn` into a new file at `target`, encrypted
+/// with `key`. Without a key the copy uses the same key as `conn`, so the
+/// copy of an encrypted database is encrypted too.
+pub fn export(conn: &Connection, target: &Path, key: Option<&str>) -> Result<(), AppError> {
+    let target = target.to_string_lossy();
+    let version = migrations::current_version(conn)?;
+    match key {
+        Some(key) => conn.execute("ATTACH DATABASE ?1 AS export KEY ?2", params![target, key]),
+        None => conn.execute("ATTACH DATABASE ?1 AS export", params![target]),
+    }?;
+
+    let result = conn
+        .query_row("SELECT sqlcipher_export('export')", [], |_| Ok(()))
+        // sqlcipher_export copies the schema and data but not the header
+        .and_then(|_| conn.pragma_update(Some(DatabaseName::Attached("export")), "user_version", version));
+    let detached = conn.execute("DETACH DATABASE export", []);
+    result?;
+    detached?;
+    Ok(())
+}
+
+pub fn file_path(conn: &Connection) -> Result<PathBuf, AppError> {
+    conn.path()
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| AppError::conflict(tr!("La base de datos no está guardada en un archivo")))
+}
+
+/// Replaces the file behind `conn` with `replacement` and reopens it with
+/// `key`. If the file cannot be replaced the original is reopened.
+pub fn replace(
+    conn: &mut Connection,
+    replacement: &Path,
+    key: Option<&str>,
+    original_key: Option<&str>,
+) -> Result<(), AppError> {
+    let path = file_path(conn)?;
+
+    // Close the live database before its file is swapped
+    *conn = Connection::open_in_memory()?;
+    match std::fs::rename(replacement, &path) {
+        Ok(()) => {
+            *conn = open(&path, key)?;
+            Ok(())
+        }
+        Err(e) => {
+            *conn = open(&path, original_key)?;
+            Err(AppError::database(tr!("No se pudo reemplazar la base de datos"), e))
+        }
+    }
+}
+
+/// Encrypts a plain database in place with `secret`.
+pub fn encrypt(conn: &mut Connection, secret: &str) -> Result<(), AppError> {
+    validate_secret(secret)?;
+    let path = file_path(conn)?;
+    if is_encrypted(&path) {
+        return Err(AppError::conflict(tr!("La base de datos ya está cifrada")));
+    }
+
+    let target = path.with_extension("db.encrypting");
+    let _ = std::fs::remove_file(&target);
+    export(conn, &target, Some(secret))?;
+    // Make sure the copy opens with the secret before the original goes away
+    open_read_only(&target, Some(secret))?;
+    replace(conn, &target, Some(secret), None)
+}
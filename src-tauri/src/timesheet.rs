@@ -0,0 +1,335 @@
+// Timesheets: pairs each employee's entry/exit rows into shifts and totals worked time.
+//
+// Punches generated by the placeholder sequence policy are stamped when the
+// real punch arrived, not when the missing one happened, so a pair that
+// includes one is reported for review instead of being counted.
+
+use crate::presence::{FLAG_CLOCK_ANOMALY, FLAG_OUT_OF_SEQUENCE, FLAG_PLACEHOLDER};
+use crate::workday::WorkdayBoundary;
+use crate::AttendanceRecord;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+pub struct Shift {
+    entry_id: i64,
+    exit_id: i64,
+    start: String,
+    end: String,
+    worked_seconds: i64,
+    /// Either punch was out of sequence or taken after a clock jump.
+    needs_review: bool,
+}
+
+#[derive(Serialize)]
+pub struct UnmatchedPunch {
+    record_id: i64,
+    timestamp: String,
+    r#type: String,
+    /// `missing_exit`, `missing_entry`, `placeholder` or `invalid_timestamp`.
+    reason: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct DayTotal {
+    date: String,
+    worked_seconds: i64,
+    shifts: Vec<Shift>,
+}
+
+#[derive(Serialize)]
+pub struct EmployeeTimesheet {
+    employee_id: String,
+    employee_name: Option<String>,
+    total_seconds: i64,
+    days: Vec<DayTotal>,
+    unmatched: Vec<UnmatchedPunch>,
+}
+
+pub fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    // Edits from the admin panel are saved without seconds
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M"))
+        .ok()
+}
+
+/// Widens a date filter by one day so shifts crossing the range edges can still be paired.
+pub fn pad_date(date: &Option<String>, days: i64) -> Option<String> {
+    date.as_ref().map(|d| {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map(|nd| (nd + chrono::Duration::days(days)).format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| d.clone())
+    })
+}
+
+struct Builder {
+    sheet: EmployeeTimesheet,
+    days: BTreeMap<NaiveDate, DayTotal>,
+    open_entry: Option<(AttendanceRecord, NaiveDateTime)>,
+}
+
+fn has_flag(record: &AttendanceRecord, flags: &[&str]) -> bool {
+    record.flag.as_deref().is_some_and(|flag| flags.contains(&flag))
+}
+
+impl Builder {
+    /// Reports a punch left out of the totals. Placeholders are reported as
+    /// such whatever else is wrong with them.
+    fn unmatched(&mut self, record: &AttendanceRecord, reason: &'static str) {
+        let reason = if has_flag(record, &[FLAG_PLACEHOLDER]) { "placeholder" } else { reason };
+        self.sheet.unmatched.push(UnmatchedPunch {
+            record_id: record.id,
+            timestamp: record.timestamp.clone(),
+            r#type: record.r#type.clone(),
+            reason,
+        });
+    }
+}
+
+/// Builds one timesheet per employee. `records` may extend past the requested
+/// range; only shifts and unmatched punches whose workday falls inside it are
+/// reported. A shift is attributed to the workday of its entry.
+pub fn build(
+    records: &[AttendanceRecord],
+    boundary: &WorkdayBoundary,
+    start_date: &Option<String>,
+    end_date: &Option<String>,
+) -> Vec<EmployeeTimesheet> {
+    let start = start_date.as_deref().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let end = end_date.as_deref().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let in_range = |date: NaiveDate| start.is_none_or(|s| date >= s) && end.is_none_or(|e| date <= e);
+
+    let mut sorted: Vec<&AttendanceRecord> = records.iter().collect();
+    sorted.sort_by(|a, b| {
+        (&a.employee_id, a.instant, a.id).cmp(&(&b.employee_id, b.instant, b.id))
+    });
+
+    let mut builders: BTreeMap<String, Builder> = BTreeMap::new();
+
+    for record in sorted {
+        let b = builders.entry(record.employee_id.clone()).or_insert_with(|| Builder {
+            sheet: EmployeeTimesheet {
+                employee_id: record.employee_id.clone(),
+                employee_name: None,
+                total_seconds: 0,
+                days: Vec::new(),
+                unmatched: Vec::new(),
+            },
+            days: BTreeMap::new(),
+            open_entry: None,
+        });
+        if record.employee_name.is_some() {
+            b.sheet.employee_name = record.employee_name.clone();
+        }
+
+        let Some(ts) = parse_timestamp(&record.timestamp) else {
+            b.unmatched(record, "invalid_timestamp");
+            continue;
+        };
+
+        match record.r#type.as_str() {
+            "entry" => {
+                if let Some((previous, prev_ts)) = b.open_entry.take() {
+                    if in_range(boundary.business_date(prev_ts)) {
+                        b.unmatched(&previous, "missing_exit");
+                    }
+                }
+                b.open_entry = Some((record.clone(), ts));
+            }
+            _ => match b.open_entry.take() {
+                Some((entry, entry_ts)) => {
+                    let day = boundary.business_date(entry_ts);
+                    if !in_range(day) {
+                        continue;
+                    }
+                    if has_flag(&entry, &[FLAG_PLACEHOLDER]) || has_flag(record, &[FLAG_PLACEHOLDER]) {
+                        b.unmatched(&entry, "placeholder");
+                        b.unmatched(record, "placeholder");
+                        continue;
+                    }
+                    // Measured between instants so a shift spanning a DST change
+                    // counts the hours actually worked
+                    let worked_seconds = match (entry.instant, record.instant) {
+                        (Some(from), Some(to)) => (to - from).num_seconds(),
+                        _ => (ts - entry_ts).num_seconds(),
+                    };
+                    let total = b.days.entry(day).or_insert_with(|| DayTotal {
+                        date: day.format("%Y-%m-%d").to_string(),
+                        worked_seconds: 0,
+                        shifts: Vec::new(),
+                    });
+                    total.worked_seconds += worked_seconds;
+                    total.shifts.push(Shift {
+                        entry_id: entry.id,
+                        exit_id: record.id,
+                        start: entry.timestamp.clone(),
+                        end: record.timestamp.clone(),
+                        worked_seconds,
+                        needs_review: [&entry, record]
+                            .iter()
+                            .any(|r| has_flag(r, &[FLAG_OUT_OF_SEQUENCE, FLAG_CLOCK_ANOMALY])),
+                    });
+                }
+                None => {
+                    if in_range(boundary.business_date(ts)) {
+                        b.unmatched(record, "missing_entry");
+                    }
+                }
+            },
+        }
+    }
+
+    builders
+        .into_values()
+        .map(|mut b| {
+            if let Some((entry, entry_ts)) = b.open_entry.take() {
+                if in_range(boundary.business_date(entry_ts)) {
+                    b.unmatched(&entry, "missing_exit");
+                }
+            }
+            let mut sheet = b.sheet;
+            sheet.days = b.days.into_values().collect();
+            sheet.total_seconds = sheet.days.iter().map(|d| d.worked_seconds).sum();
+            sheet
+        })
+        .filter(|sheet| !sheet.days.is_empty() || !sheet.unmatched.is_empty())
+        .collect()
+}
+
+/// Punch by employee 001 at site wall-clock `timestamp`, for tests.
+#[cfg(test)]
+pub(crate) fn test_record(
+    id: i64,
+    r#type: &str,
+    timestamp: &str,
+    flag: Option<&str>,
+) -> AttendanceRecord {
+    AttendanceRecord {
+        id,
+        employee_id: "001".to_string(),
+        employee_name: Some("Ana".to_string()),
+        timestamp: timestamp.to_string(),
+        r#type: r#type.to_string(),
+        notes: None,
+        created_at: timestamp.to_string(),
+        updated_at: timestamp.to_string(),
+        flag: flag.map(str::to_string),
+        late_minutes: None,
+        early_departure_minutes: None,
+        deleted_at: None,
+        deleted_by: None,
+        source: "kiosk".to_string(),
+        device_id: None,
+        instant: parse_timestamp(timestamp).map(|ts| ts.and_utc()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_all(records: &[AttendanceRecord], start: &str) -> EmployeeTimesheet {
+        let mut sheets = build(records, &WorkdayBoundary::starting_at(start), &None, &None);
+        assert_eq!(sheets.len(), 1);
+        sheets.remove(0)
+    }
+
+    fn reasons(sheet: &EmployeeTimesheet) -> Vec<&'static str> {
+        sheet.unmatched.iter().map(|u| u.reason).collect()
+    }
+
+    #[test]
+    fn pairs_entries_with_the_next_exit() {
+        let sheet = build_all(
+            &[
+                test_record(1, "entry", "2024-03-04 08:00:00", None),
+                test_record(2, "exit", "2024-03-04 12:00:00", None),
+                test_record(3, "entry", "2024-03-04 13:00:00", None),
+                test_record(4, "exit", "2024-03-04 17:30:00", None),
+            ],
+            "00:00",
+        );
+        assert_eq!(sheet.total_seconds, (8 * 60 + 30) * 60);
+        assert_eq!(sheet.days.len(), 1);
+        assert_eq!(sheet.days[0].shifts.len(), 2);
+        assert!(sheet.unmatched.is_empty());
+    }
+
+    #[test]
+    fn night_shift_counts_for_the_day_it_started() {
+        let sheet = build_all(
+            &[
+                test_record(1, "entry", "2024-03-04 22:00:00", None),
+                test_record(2, "exit", "2024-03-05 06:00:00", None),
+            ],
+            "05:00",
+        );
+        assert_eq!(sheet.days.len(), 1);
+        assert_eq!(sheet.days[0].date, "2024-03-04");
+        assert_eq!(sheet.total_seconds, 8 * 3600);
+    }
+
+    #[test]
+    fn unpaired_punches_are_reported() {
+        let sheet = build_all(
+            &[
+                test_record(1, "exit", "2024-03-04 07:00:00", None),
+                test_record(2, "entry", "2024-03-04 08:00:00", None),
+                test_record(3, "entry", "2024-03-04 09:00:00", None),
+                test_record(4, "exit", "2024-03-04 17:00:00", None),
+                test_record(5, "entry", "2024-03-05 08:00:00", None),
+            ],
+            "00:00",
+        );
+        assert_eq!(sheet.total_seconds, 8 * 3600);
+        assert_eq!(reasons(&sheet), ["missing_entry", "missing_exit", "missing_exit"]);
+    }
+
+    #[test]
+    fn placeholder_pairs_are_not_counted() {
+        let sheet = build_all(
+            &[
+                test_record(1, "entry", "2024-03-04 08:00:00", None),
+                // Generated when the next entry came days later
+                test_record(2, "exit", "2024-03-07 08:00:00", Some(FLAG_PLACEHOLDER)),
+                test_record(3, "entry", "2024-03-07 08:00:00", None),
+                test_record(4, "exit", "2024-03-07 16:00:00", None),
+            ],
+            "00:00",
+        );
+        assert_eq!(sheet.total_seconds, 8 * 3600);
+        assert_eq!(reasons(&sheet), ["placeholder", "placeholder"]);
+    }
+
+    #[test]
+    fn flagged_shifts_need_review() {
+        let sheet = build_all(
+            &[
+                test_record(1, "entry", "2024-03-04 08:00:00", Some(FLAG_OUT_OF_SEQUENCE)),
+                test_record(2, "exit", "2024-03-04 16:00:00", None),
+                test_record(3, "entry", "2024-03-05 08:00:00", None),
+                test_record(4, "exit", "2024-03-05 16:00:00", None),
+            ],
+            "00:00",
+        );
+        let review: Vec<bool> = sheet.days.iter().map(|d| d.shifts[0].needs_review).collect();
+        assert_eq!(review, [true, false]);
+        assert_eq!(sheet.total_seconds, 16 * 3600);
+    }
+
+    #[test]
+    fn range_keeps_only_its_workdays() {
+        let records = [
+            test_record(1, "entry", "2024-03-03 22:00:00", None),
+            test_record(2, "exit", "2024-03-04 06:00:00", None),
+            test_record(3, "entry", "2024-03-04 22:00:00", None),
+            test_record(4, "exit", "2024-03-05 06:00:00", None),
+        ];
+        let day = Some("2024-03-04".to_string());
+        let sheets = build(&records, &WorkdayBoundary::starting_at("05:00"), &day, &day);
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].days.len(), 1);
+        assert_eq!(sheets[0].days[0].shifts[0].entry_id, 3);
+    }
+}
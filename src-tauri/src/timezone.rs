@@ -0,0 +1,182 @@
+// Site timezone. Instants are stored in UTC as "YYYY-MM-DD HH:MM:SS", which
+// sorts and compares correctly in SQL across DST changes, and are converted to
+// the site's wall-clock time only at the edges: when rows are read for the
+// frontend, reports and exports, and when an admin types in a time.
+//
+// The site timezone is an IANA name in `config`, defaulting to the computer's
+// own. Like the locale it is kept process-wide once loaded, since rows are
+// converted far from the app state.
+
+use crate::config;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::timesheet::parse_timestamp;
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::Connection;
+use std::sync::RwLock;
+
+const TIMEZONE_KEY: &str = "timezone";
+pub const STORAGE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Used when the computer's timezone cannot be determined.
+const FALLBACK: Tz = chrono_tz::America::Mexico_City;
+/// Longest stretch of wall-clock time skipped by a DST change.
+const MAX_GAP_MINUTES: i64 = 3 * 60;
+
+static CURRENT: RwLock<Tz> = RwLock::new(FALLBACK);
+
+pub fn current() -> Tz {
+    *CURRENT.read().unwrap()
+}
+
+/// The computer's timezone.
+pub fn system() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(FALLBACK)
+}
+
+/// The configured timezone, or the computer's when none is configured.
+pub fn configured(conn: &Connection) -> rusqlite::Result<Tz> {
+    Ok(config::get(conn, TIMEZONE_KEY)?
+        .and_then(|name| name.parse().ok())
+        .unwrap_or_else(system))
+}
+
+pub fn load(conn: &Connection) -> rusqlite::Result<()> {
+    *CURRENT.write().unwrap() = configured(conn)?;
+    Ok(())
+}
+
+pub fn save(conn: &Connection, name: &str) -> Result<(), AppError> {
+    let tz: Tz = name
+        .trim()
+        .parse()
+        .map_err(|_| AppError::validation(tr!("Zona horaria inválida: {}", name)))?;
+    config::set(conn, TIMEZONE_KEY, tz.name())?;
+    *CURRENT.write().unwrap() = tz;
+    Ok(())
+}
+
+/// IANA names offered in the settings.
+pub fn names() -> Vec<&'static str> {
+    chrono_tz::TZ_VARIANTS.iter().map(|tz| tz.name()).collect()
+}
+
+pub fn parse_utc(stored: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(stored, STORAGE_FORMAT)
+        .ok()
+        .map(|ts| ts.and_utc())
+}
+
+pub fn format_utc(instant: DateTime<Utc>) -> String {
+    instant.format(STORAGE_FORMAT).to_string()
+}
+
+pub fn to_local(instant: DateTime<Utc>) -> NaiveDateTime {
+    instant.with_timezone(&current()).naive_local()
+}
+
+/// A stored UTC timestamp as site wall-clock time. Values that do not parse
+/// are returned unchanged.
+pub fn display(stored: &str) -> String {
+    parse_utc(stored)
+        .map(|instant| to_local(instant).format(STORAGE_FORMAT).to_string())
+        .unwrap_or_else(|| stored.to_string())
+}
+
+pub fn now_local() -> NaiveDateTime {
+    to_local(Utc::now())
+}
+
+/// The instant a site wall-clock time refers to. In the hour repeated when DST
+/// ends the earlier instant is taken; a time skipped when DST starts does not
+/// exist and gives `None`.
+pub fn to_utc(local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    to_utc_in(current(), local)
+}
+
+pub fn to_utc_in(tz: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) => Some(dt.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.with_timezone(&Utc)),
+        LocalResult::None => None,
+    }
+}
+
+/// First instant whose wall-clock time is at or after `local`, for the edges
+/// of date ranges. A time skipped when DST starts maps to the moment the
+/// clocks jumped.
+pub fn boundary_utc(local: NaiveDateTime) -> DateTime<Utc> {
+    (0..=MAX_GAP_MINUTES)
+        .find_map(|m| to_utc(local + Duration::minutes(m)))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// Parses a wall-clock time typed by an admin ("YYYY-MM-DD HH:MM[:SS]").
+pub fn parse_input(value: &str) -> Result<DateTime<Utc>, AppError> {
+    let local = parse_timestamp(value).ok_or_else(|| {
+        AppError::validation(tr!("Fecha y hora inválidas, se esperaba AAAA-MM-DD HH:MM"))
+    })?;
+    to_utc(local).ok_or_else(|| {
+        AppError::validation(tr!(
+            "La hora {} no existe en la zona horaria {} por el cambio de horario",
+            value,
+            current().name()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::{Mexico_City, New_York};
+
+    fn local(value: &str) -> NaiveDateTime {
+        parse_timestamp(value).unwrap()
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        parse_utc(value).unwrap()
+    }
+
+    #[test]
+    fn storage_format_round_trips() {
+        let instant = utc("2024-03-10 07:05:09");
+        assert_eq!(format_utc(instant), "2024-03-10 07:05:09");
+        assert!(parse_utc("2024-03-10 07:05").is_none());
+    }
+
+    #[test]
+    fn converts_wall_clock_time_to_utc() {
+        assert_eq!(
+            to_utc_in(Mexico_City, local("2024-01-15 08:00:00")),
+            Some(utc("2024-01-15 14:00:00"))
+        );
+        assert_eq!(
+            to_utc_in(New_York, local("2024-07-01 08:00")),
+            Some(utc("2024-07-01 12:00:00"))
+        );
+    }
+
+    #[test]
+    fn skipped_time_does_not_exist() {
+        // Clocks went from 02:00 to 03:00 in New York
+        assert_eq!(to_utc_in(New_York, local("2024-03-10 02:30")), None);
+    }
+
+    #[test]
+    fn repeated_time_takes_the_earlier_instant() {
+        // 01:00-02:00 happened twice in New York, first in EDT (UTC-4)
+        assert_eq!(
+            to_utc_in(New_York, local("2024-11-03 01:30")),
+            Some(utc("2024-11-03 05:30:00"))
+        );
+    }
+
+    #[test]
+    fn unparseable_values_are_displayed_as_is() {
+        assert_eq!(display("ayer"), "ayer");
+    }
+}
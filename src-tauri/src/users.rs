@@ -0,0 +1,276 @@
+// Admin accounts and roles. Every privileged command in main.rs names the
+// permission it needs; roles are fixed bundles of permissions:
+//
+//   owner       everything, including managing accounts
+//   admin       everything except managing accounts
+//   supervisor  view and export records and reports
+//   auditor     view and export records, reports and the audit log
+
+use crate::auth;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::timezone;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Owner,
+    Admin,
+    Supervisor,
+    Auditor,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ViewRecords,
+    ExportRecords,
+    EditRecords,
+    DeleteRecords,
+    ManageEmployees,
+    ManageSettings,
+    ManageUsers,
+    ViewAudit,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 8] = [
+        Permission::ViewRecords,
+        Permission::ExportRecords,
+        Permission::EditRecords,
+        Permission::DeleteRecords,
+        Permission::ManageEmployees,
+        Permission::ManageSettings,
+        Permission::ManageUsers,
+        Permission::ViewAudit,
+    ];
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Admin => "admin",
+            Role::Supervisor => "supervisor",
+            Role::Auditor => "auditor",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Role> {
+        match value {
+            "owner" => Some(Role::Owner),
+            "admin" => Some(Role::Admin),
+            "supervisor" => Some(Role::Supervisor),
+            "auditor" => Some(Role::Auditor),
+            _ => None,
+        }
+    }
+
+    pub fn allows(self, permission: Permission) -> bool {
+        match self {
+            Role::Owner => true,
+            Role::Admin => permission != Permission::ManageUsers,
+            Role::Supervisor => matches!(
+                permission,
+                Permission::ViewRecords | Permission::ExportRecords
+            ),
+            Role::Auditor => matches!(
+                permission,
+                Permission::ViewRecords | Permission::ExportRecords | Permission::ViewAudit
+            ),
+        }
+    }
+
+    pub fn permissions(self) -> Vec<Permission> {
+        Permission::ALL
+            .into_iter()
+            .filter(|p| self.allows(*p))
+            .collect()
+    }
+}
+
+/// The account behind a session, as seen by commands and the frontend.
+#[derive(Serialize, Clone)]
+pub struct SessionUser {
+    pub id: i64,
+    pub username: String,
+    pub display_name: String,
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize)]
+pub struct UserAccount {
+    id: i64,
+    username: String,
+    display_name: String,
+    role: Role,
+    active: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+fn parse_role(value: &str) -> Result<Role, AppError> {
+    Role::parse(value).ok_or_else(|| AppError::validation(tr!("Rol inválido: {}", value)))
+}
+
+fn row_to_role(value: String) -> rusqlite::Result<Role> {
+    Role::parse(&value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            0,
+            rusqlite::types::Type::Text,
+            format!("unknown role {}", value).into(),
+        )
+    })
+}
+
+pub fn has_users(conn: &Connection) -> rusqlite::Result<bool> {
+    conn.query_row("SELECT EXISTS(SELECT 1 FROM users)", [], |row| row.get(0))
+}
+
+pub fn get_users(conn: &Connection) -> rusqlite::Result<Vec<UserAccount>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, username, display_name, role, active, created_at, updated_at
+         FROM users ORDER BY username",
+    )?;
+    let users = stmt
+        .query_map([], |row| {
+            Ok(UserAccount {
+                id: row.get(0)?,
+                username: row.get(1)?,
+                display_name: row.get(2)?,
+                role: row_to_role(row.get(3)?)?,
+                active: row.get(4)?,
+                created_at: timezone::display(&row.get::<_, String>(5)?),
+                updated_at: timezone::display(&row.get::<_, String>(6)?),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(users)
+}
+
+pub fn get_session_user(conn: &Connection, id: i64) -> rusqlite::Result<Option<SessionUser>> {
+    conn.query_row(
+        "SELECT id, username, display_name, role FROM users WHERE id = ?1 AND active = 1",
+        params![id],
+        |row| {
+            let role = row_to_role(row.get(3)?)?;
+            Ok(SessionUser {
+                id: row.get(0)?,
+                username: row.get(1)?,
+                display_name: row.get(2)?,
+                role,
+                permissions: role.permissions(),
+            })
+        },
+    )
+    .optional()
+}
+
+fn validate_account(username: &str, display_name: &str) -> Result<(), AppError> {
+    if username.trim().is_empty() {
+        return Err(AppError::validation(tr!("El nombre de usuario no puede estar vacío")));
+    }
+    if username.trim().contains(char::is_whitespace) {
+        return Err(AppError::validation(tr!("El nombre de usuario no puede contener espacios")));
+    }
+    if display_name.trim().is_empty() {
+        return Err(AppError::validation(tr!("El nombre no puede estar vacío")));
+    }
+    Ok(())
+}
+
+pub fn create_user(
+    conn: &Connection,
+    username: &str,
+    display_name: &str,
+    password: &str,
+    role: &str,
+) -> Result<i64, AppError> {
+    validate_account(username, display_name)?;
+    let role = parse_role(role)?;
+    auth::validate_new_password(password)?;
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE username = ?1)",
+            params![username.trim()],
+            |row| row.get(0),
+        )?;
+    if exists {
+        return Err(AppError::conflict(tr!("El usuario {} ya existe", username.trim())));
+    }
+
+    conn.execute(
+        "INSERT INTO users (username, display_name, password_hash, role) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            username.trim(),
+            display_name.trim(),
+            auth::hash_password(password)?,
+            role.as_str()
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn active_owner_count(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM users WHERE role = 'owner' AND active = 1",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Updates name, role and active flag. The last active owner cannot be
+/// demoted or deactivated, so the accounts can always be managed.
+pub fn update_user(
+    conn: &Connection,
+    id: i64,
+    display_name: &str,
+    role: &str,
+    active: bool,
+) -> Result<(), AppError> {
+    if display_name.trim().is_empty() {
+        return Err(AppError::validation(tr!("El nombre no puede estar vacío")));
+    }
+    let role = parse_role(role)?;
+
+    let current: Option<(String, bool)> = conn
+        .query_row(
+            "SELECT role, active FROM users WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((current_role, current_active)) = current else {
+        return Err(AppError::not_found(tr!("Usuario no encontrado")));
+    };
+
+    let was_owner = current_role == Role::Owner.as_str() && current_active;
+    let stays_owner = role == Role::Owner && active;
+    if was_owner && !stays_owner && active_owner_count(conn)? <= 1 {
+        return Err(AppError::conflict(tr!("Debe existir al menos un propietario activo")));
+    }
+
+    conn.execute(
+        "UPDATE users SET display_name = ?1, role = ?2, active = ?3, updated_at = datetime('now')
+         WHERE id = ?4",
+        params![display_name.trim(), role.as_str(), active, id],
+    )?;
+    Ok(())
+}
+
+pub fn reset_password(conn: &Connection, id: i64, new_password: &str) -> Result<(), AppError> {
+    auth::validate_new_password(new_password)?;
+    let rows_affected = conn
+        .execute(
+            "UPDATE users SET password_hash = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![auth::hash_password(new_password)?, id],
+        )?;
+    if rows_affected == 0 {
+        return Err(AppError::not_found(tr!("Usuario no encontrado")));
+    }
+    Ok(())
+}
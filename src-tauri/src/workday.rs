@@ -0,0 +1,105 @@
+// Business-day boundary: punches before the configured start time belong to the
+// previous workday, so a 22:00-06:00 shift is attributed to the day it started.
+// Workdays are measured in site wall-clock time; `day_start` gives the UTC
+// instant a workday begins, for filtering stored timestamps.
+
+use crate::config;
+use crate::error::AppError;
+use crate::i18n::tr;
+use crate::timezone;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::Connection;
+
+const WORKDAY_START_KEY: &str = "workday_start";
+
+#[derive(Clone, Copy)]
+pub struct WorkdayBoundary {
+    minutes: i64,
+}
+
+impl WorkdayBoundary {
+    pub fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let minutes = config::get(conn, WORKDAY_START_KEY)?
+            .as_deref()
+            .and_then(parse_time)
+            .unwrap_or(0);
+        Ok(WorkdayBoundary { minutes })
+    }
+
+    pub fn save(conn: &Connection, value: &str) -> Result<(), AppError> {
+        if parse_time(value).is_none() {
+            return Err(AppError::validation(tr!("Formato de hora inválido, se esperaba HH:MM")));
+        }
+        config::set(conn, WORKDAY_START_KEY, value).map_err(AppError::from)
+    }
+
+    /// Boundary at `start` (HH:MM) without going through the settings.
+    #[cfg(test)]
+    pub(crate) fn starting_at(start: &str) -> Self {
+        WorkdayBoundary { minutes: parse_time(start).unwrap() }
+    }
+
+    pub fn start_time(&self) -> String {
+        format!("{:02}:{:02}", self.minutes / 60, self.minutes % 60)
+    }
+
+    /// Workday a site wall-clock time belongs to.
+    pub fn business_date(&self, ts: NaiveDateTime) -> NaiveDate {
+        (ts - Duration::minutes(self.minutes)).date()
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.business_date(timezone::now_local())
+    }
+
+    /// Stored (UTC) timestamp at which `day` begins. A workday lasts from its
+    /// start to the next one's, so it is 23 or 25 hours long across DST changes.
+    pub fn day_start(&self, day: NaiveDate) -> String {
+        let local = day.and_time(NaiveTime::MIN) + Duration::minutes(self.minutes);
+        timezone::format_utc(timezone::boundary_utc(local))
+    }
+}
+
+fn parse_time(value: &str) -> Option<i64> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .ok()
+        .map(|t| (t - NaiveTime::MIN).num_minutes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn times_before_the_start_belong_to_the_previous_day() {
+        let boundary = WorkdayBoundary::starting_at("05:30");
+        assert_eq!(boundary.business_date(at("2024-03-05 05:29")), date("2024-03-04"));
+        assert_eq!(boundary.business_date(at("2024-03-05 05:30")), date("2024-03-05"));
+        assert_eq!(boundary.business_date(at("2024-03-05 23:59")), date("2024-03-05"));
+    }
+
+    #[test]
+    fn midnight_start_keeps_calendar_days() {
+        let boundary = WorkdayBoundary::starting_at("00:00");
+        assert_eq!(boundary.business_date(at("2024-03-05 00:00")), date("2024-03-05"));
+        assert_eq!(boundary.start_time(), "00:00");
+    }
+
+    #[test]
+    fn start_must_be_a_time_of_day() {
+        let conn = migrations::test_db();
+        for value in ["5", "25:00", "05:30:00", ""] {
+            assert!(WorkdayBoundary::save(&conn, value).is_err(), "{} was accepted", value);
+        }
+        assert_eq!(WorkdayBoundary::load(&conn).unwrap().start_time(), "00:00");
+    }
+}
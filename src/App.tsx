@@ -2,74 +2,168 @@ import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import KioskView from './components/KioskView';
 import AdminPanel from './components/AdminPanel';
+import RecoveryScreen from './components/RecoveryScreen';
+import UnlockScreen from './components/UnlockScreen';
+import type { LoginResponse, RecoveryStatus } from './types/attendance';
+import { describeError } from './types/errors';
 import './App.css';
 
 function App() {
   const [mode, setMode] = useState<'kiosk' | 'admin'>('kiosk');
+  const [session, setSession] = useState<LoginResponse | null>(null);
   const [showPasswordModal, setShowPasswordModal] = useState(false);
+  const [needsSetup, setNeedsSetup] = useState(false);
+  const [username, setUsername] = useState('');
+  const [displayName, setDisplayName] = useState('');
   const [password, setPassword] = useState('');
+  const [confirmPassword, setConfirmPassword] = useState('');
   const [authError, setAuthError] = useState('');
-  const passwordInputRef = useRef<HTMLInputElement>(null);
+  const [recovery, setRecovery] = useState<RecoveryStatus | null>(null);
+  const usernameInputRef = useRef<HTMLInputElement>(null);
+
+  useEffect(() => {
+    invoke<RecoveryStatus>('get_recovery_status')
+      .then(setRecovery)
+      .catch(() => {});
+  }, []);
 
   useEffect(() => {
     const handleKeyPress = (e: KeyboardEvent) => {
       if (e.key === 'F12' && mode === 'kiosk') {
         e.preventDefault();
-        setShowPasswordModal(true);
-        setPassword('');
-        setAuthError('');
+        openPasswordModal();
       }
       if (e.key === 'Escape') {
         if (showPasswordModal) {
           setShowPasswordModal(false);
         } else if (mode === 'admin') {
-          setMode('kiosk');
+          handleBackToKiosk();
         }
       }
     };
 
     window.addEventListener('keydown', handleKeyPress);
     return () => window.removeEventListener('keydown', handleKeyPress);
-  }, [mode, showPasswordModal]);
+  }, [mode, showPasswordModal, session]);
 
   useEffect(() => {
-    if (showPasswordModal && passwordInputRef.current) {
-      passwordInputRef.current.focus();
+    if (showPasswordModal && usernameInputRef.current) {
+      usernameInputRef.current.focus();
     }
   }, [showPasswordModal]);
 
+  const openPasswordModal = async () => {
+    setUsername('');
+    setDisplayName('');
+    setPassword('');
+    setConfirmPassword('');
+    setAuthError('');
+    try {
+      const status = await invoke<{ setup_required: boolean }>('get_auth_status');
+      setNeedsSetup(status.setup_required);
+    } catch (err) {
+      setAuthError(describeError(err));
+    }
+    setShowPasswordModal(true);
+  };
+
   const handlePasswordSubmit = async () => {
-    if (!password.trim()) return;
+    if (!username.trim() || !password.trim()) return;
+
+    if (needsSetup && password !== confirmPassword) {
+      setAuthError('Las contraseñas no coinciden');
+      return;
+    }
 
     try {
-      const valid = await invoke<boolean>('verify_admin_password', { password });
-      if (valid) {
-        setMode('admin');
-        setShowPasswordModal(false);
-        setPassword('');
-        setAuthError('');
-      } else {
-        setAuthError('Contraseña incorrecta.');
-      }
+      const response = needsSetup
+        ? await invoke<LoginResponse>('setup_owner_account', {
+            username,
+            displayName: displayName || username,
+            password,
+          })
+        : await invoke<LoginResponse>('admin_login', { username, password });
+      setSession(response);
+      setNeedsSetup(false);
+      setMode('admin');
+      // Automatic backups may have failed since startup
+      invoke<RecoveryStatus>('get_recovery_status')
+        .then(setRecovery)
+        .catch(() => {});
+      setShowPasswordModal(false);
+      setPassword('');
+      setConfirmPassword('');
+      setAuthError('');
     } catch (err) {
-      setAuthError(`Error al verificar: ${err}`);
+      setAuthError(describeError(err));
     }
   };
 
+  const handleBackToKiosk = () => {
+    if (session) {
+      invoke('admin_logout', { token: session.token }).catch(() => {});
+    }
+    setSession(null);
+    setMode('kiosk');
+  };
+
+  if (recovery?.locked) {
+    return (
+      <div className="app">
+        <UnlockScreen onUnlocked={setRecovery} />
+      </div>
+    );
+  }
+
+  if (recovery?.active) {
+    return (
+      <div className="app">
+        <RecoveryScreen status={recovery} />
+      </div>
+    );
+  }
+
   return (
     <div className="app">
-      {mode === 'kiosk' ? (
+      {mode === 'kiosk' || !session ? (
         <KioskView />
       ) : (
-        <AdminPanel onBack={() => setMode('kiosk')} />
+        <>
+          {recovery?.backup_error && (
+            <p className="backup-warning">
+              ⚠️ No se pudo crear el respaldo automático: {recovery.backup_error}
+            </p>
+          )}
+          <AdminPanel token={session.token} user={session.user} onBack={handleBackToKiosk} />
+        </>
       )}
 
       {showPasswordModal && (
         <div className="confirm-overlay" onClick={() => setShowPasswordModal(false)}>
           <div className="confirm-dialog" onClick={(e) => e.stopPropagation()}>
-            <p className="confirm-message">Contraseña de administrador:</p>
+            <p className="confirm-message">
+              {needsSetup
+                ? 'Crea la cuenta del propietario:'
+                : 'Acceso de administrador:'}
+            </p>
+            <input
+              ref={usernameInputRef}
+              type="text"
+              className="password-input"
+              value={username}
+              onChange={(e) => setUsername(e.target.value)}
+              placeholder="Usuario..."
+            />
+            {needsSetup && (
+              <input
+                type="text"
+                className="password-input"
+                value={displayName}
+                onChange={(e) => setDisplayName(e.target.value)}
+                placeholder="Nombre..."
+              />
+            )}
             <input
-              ref={passwordInputRef}
               type="password"
               className="password-input"
               value={password}
@@ -79,13 +173,25 @@ function App() {
               }}
               placeholder="Contraseña..."
             />
+            {needsSetup && (
+              <input
+                type="password"
+                className="password-input"
+                value={confirmPassword}
+                onChange={(e) => setConfirmPassword(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter') handlePasswordSubmit();
+                }}
+                placeholder="Confirmar contraseña..."
+              />
+            )}
             {authError && <p className="auth-error">{authError}</p>}
             <div className="confirm-actions">
               <button className="btn-cancel" onClick={() => setShowPasswordModal(false)}>
                 Cancelar
               </button>
               <button className="btn-confirm" onClick={handlePasswordSubmit}>
-                Ingresar
+                {needsSetup ? 'Guardar' : 'Ingresar'}
               </button>
             </div>
           </div>
@@ -0,0 +1,119 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { EmployeeAbsences } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface AbsenceReportProps {
+    token: string;
+}
+
+export default function AbsenceReport({ token }: AbsenceReportProps) {
+    const [startDate, setStartDate] = useState("");
+    const [endDate, setEndDate] = useState("");
+    const [employeeId, setEmployeeId] = useState("");
+    const [report, setReport] = useState<EmployeeAbsences[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const loadReport = async () => {
+        if (!startDate || !endDate) {
+            setError("Selecciona la fecha de inicio y fin");
+            return;
+        }
+        setLoading(true);
+        setError(null);
+        try {
+            const result = await invoke<EmployeeAbsences[]>("get_absences", {
+                token,
+                startDate,
+                endDate,
+                employeeId: employeeId || null,
+            });
+            setReport(result);
+        } catch (err) {
+            setError(`Error al calcular ausencias: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Ausencias</h2>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+
+            <section className="filters-section">
+                <div className="filter-group">
+                    <label htmlFor="absence-start">Fecha Inicio</label>
+                    <input
+                        id="absence-start"
+                        type="date"
+                        className="filter-input"
+                        value={startDate}
+                        onChange={(e) => setStartDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="absence-end">Fecha Fin</label>
+                    <input
+                        id="absence-end"
+                        type="date"
+                        className="filter-input"
+                        value={endDate}
+                        onChange={(e) => setEndDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="absence-employee">ID Empleado</label>
+                    <input
+                        id="absence-employee"
+                        type="text"
+                        className="filter-input"
+                        placeholder="Todos"
+                        value={employeeId}
+                        onChange={(e) => setEmployeeId(e.target.value)}
+                    />
+                </div>
+                <div className="filter-actions">
+                    <button className="btn-search" onClick={loadReport}>
+                        Calcular
+                    </button>
+                </div>
+            </section>
+
+            <div className="employees-section">
+                {loading ? (
+                    <div className="loading">Calculando...</div>
+                ) : report.length === 0 ? (
+                    <div className="no-records">
+                        Sin resultados. Las ausencias se calculan con el horario de cada empleado.
+                    </div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Empleado</th>
+                                <th>Días laborales</th>
+                                <th>Faltas</th>
+                                <th>Fechas</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {report.map((row) => (
+                                <tr key={row.employee_id}>
+                                    <td>{row.employee_name}</td>
+                                    <td>{row.expected_days}</td>
+                                    <td>{row.absent_days.length}</td>
+                                    <td>{row.absent_days.join(", ") || "-"}</td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
@@ -1,17 +1,47 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { format } from "date-fns";
-import { FiEdit2, FiTrash2, FiX, FiRefreshCw, FiDownload, FiUsers, FiList } from "react-icons/fi";
+import { FiEdit2, FiTrash2, FiX, FiRefreshCw, FiDownload, FiUsers, FiList, FiSettings, FiClock, FiAlertCircle, FiUserX, FiShield, FiFileText, FiArchive, FiPlus, FiWatch } from "react-icons/fi";
 import ConfirmDialog from "./ConfirmDialog";
 import EmployeeManagement from "./EmployeeManagement";
-import type { AttendanceRecord, RecordFilter, DailyStats } from "../types/attendance";
+import SettingsPanel from "./SettingsPanel";
+import TimesheetView from "./TimesheetView";
+import TardinessReport from "./TardinessReport";
+import AbsenceReport from "./AbsenceReport";
+import UserManagement, { ROLE_LABELS } from "./UserManagement";
+import AuditLog from "./AuditLog";
+import ClockAnomalyReport from "./ClockAnomalyReport";
+import RecycleBinView from "./RecycleBinView";
+import ManualRecordDialog from "./ManualRecordDialog";
+import type {
+  AttendanceRecord,
+  RecordFilter,
+  RecordSource,
+  DailyStats,
+  Permission,
+  SessionUser,
+} from "../types/attendance";
+import { describeError } from "../types/errors";
+
+const SOURCE_LABELS: Record<RecordSource, string> = {
+  kiosk: "Kiosco",
+  manual: "Manual",
+  import: "Importación",
+  auto_close: "Cierre automático",
+  api: "API",
+};
 
 interface AdminPanelProps {
+  token: string;
+  user: SessionUser;
   onBack: () => void;
 }
 
-export default function AdminPanel({ onBack }: AdminPanelProps) {
-  const [activeTab, setActiveTab] = useState<"attendance" | "employees">("attendance");
+export default function AdminPanel({ token, user, onBack }: AdminPanelProps) {
+  const [activeTab, setActiveTab] = useState<
+    "attendance" | "timesheet" | "tardiness" | "absences" | "employees" | "users" | "audit" | "clock" | "trash" | "settings"
+  >("attendance");
+  const can = (permission: Permission) => user.permissions.includes(permission);
   const [records, setRecords] = useState<AttendanceRecord[]>([]);
   const [stats, setStats] = useState<DailyStats | null>(null);
   const [filters, setFilters] = useState<RecordFilter>({});
@@ -24,9 +54,14 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
   const [editTimestamp, setEditTimestamp] = useState("");
   const [editType, setEditType] = useState<"entry" | "exit">("entry");
   const [editNotes, setEditNotes] = useState("");
+  const [editReason, setEditReason] = useState("");
 
   // Delete confirmation state
   const [deleteId, setDeleteId] = useState<number | null>(null);
+  const [deleteReason, setDeleteReason] = useState("");
+
+  // Manual record state
+  const [showManualRecord, setShowManualRecord] = useState(false);
 
   const clearMessages = () => {
     setError(null);
@@ -35,31 +70,26 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
 
   const loadStats = useCallback(async () => {
     try {
-      const result = await invoke<DailyStats>("get_daily_stats");
+      const result = await invoke<DailyStats>("get_daily_stats", { token });
       setStats(result);
     } catch (err) {
       console.error("Error al cargar estadisticas:", err);
     }
-  }, []);
+  }, [token]);
 
   const loadRecords = useCallback(async (filterParams?: RecordFilter) => {
     setLoading(true);
     clearMessages();
     try {
       const f = filterParams || {};
-      const result = await invoke<AttendanceRecord[]>("get_records", {
-        startDate: f.start_date || null,
-        endDate: f.end_date || null,
-        employeeId: f.employee_id || null,
-        recordType: f.type || null,
-      });
+      const result = await invoke<AttendanceRecord[]>("get_records", { token, filters: f });
       setRecords(result);
     } catch (err) {
-      setError(`Error al cargar registros: ${err}`);
+      setError(`Error al cargar registros: ${describeError(err)}`);
     } finally {
       setLoading(false);
     }
-  }, []);
+  }, [token]);
 
   useEffect(() => {
     loadStats();
@@ -82,6 +112,7 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
     setEditTimestamp(record.timestamp);
     setEditType(record.type);
     setEditNotes(record.notes || "");
+    setEditReason("");
   };
 
   const cancelEdit = () => {
@@ -89,6 +120,7 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
     setEditTimestamp("");
     setEditType("entry");
     setEditNotes("");
+    setEditReason("");
   };
 
   const saveEdit = async () => {
@@ -96,17 +128,19 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
     clearMessages();
     try {
       await invoke("update_record", {
+        token,
         id: editingId,
         timestamp: editTimestamp,
         recordType: editType,
         notes: editNotes || null,
+        reason: editReason || null,
       });
       setSuccessMessage("Registro actualizado correctamente.");
       cancelEdit();
       loadRecords(filters);
       loadStats();
     } catch (err) {
-      setError(`Error al actualizar registro: ${err}`);
+      setError(`Error al actualizar registro: ${describeError(err)}`);
     }
   };
 
@@ -115,13 +149,14 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
     if (deleteId === null) return;
     clearMessages();
     try {
-      await invoke("delete_record", { id: deleteId });
+      await invoke("delete_record", { token, id: deleteId, reason: deleteReason });
       setSuccessMessage("Registro eliminado correctamente.");
       setDeleteId(null);
+      setDeleteReason("");
       loadRecords(filters);
       loadStats();
     } catch (err) {
-      setError(`Error al eliminar registro: ${err}`);
+      setError(`Error al eliminar registro: ${describeError(err)}`);
     }
   };
 
@@ -130,14 +165,12 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
     clearMessages();
     try {
       const filePath = await invoke<string>("export_to_excel", {
-        startDate: filters.start_date || null,
-        endDate: filters.end_date || null,
-        employeeId: filters.employee_id || null,
-        recordType: filters.type || null,
+        token,
+        filters,
       });
       setSuccessMessage(`Archivo exportado: ${filePath}`);
     } catch (err) {
-      setError(`Error al exportar: ${err}`);
+      setError(`Error al exportar: ${describeError(err)}`);
     }
   };
 
@@ -161,6 +194,9 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
     <div className="admin-panel">
       <header className="admin-header">
         <h1>Panel Administrativo</h1>
+        <span className="session-user">
+          {user.display_name} · {ROLE_LABELS[user.role]}
+        </span>
         <button className="btn-close" onClick={onBack} title="Cerrar">
           <FiX size={24} />
         </button>
@@ -175,16 +211,94 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
           <FiList size={18} /> Registros de Asistencia
         </button>
         <button
-          className={`tab-button ${activeTab === "employees" ? "active" : ""}`}
-          onClick={() => setActiveTab("employees")}
+          className={`tab-button ${activeTab === "timesheet" ? "active" : ""}`}
+          onClick={() => setActiveTab("timesheet")}
+        >
+          <FiClock size={18} /> Horas Trabajadas
+        </button>
+        <button
+          className={`tab-button ${activeTab === "tardiness" ? "active" : ""}`}
+          onClick={() => setActiveTab("tardiness")}
+        >
+          <FiAlertCircle size={18} /> Retardos
+        </button>
+        <button
+          className={`tab-button ${activeTab === "absences" ? "active" : ""}`}
+          onClick={() => setActiveTab("absences")}
+        >
+          <FiUserX size={18} /> Ausencias
+        </button>
+        {can("manage_employees") && (
+          <button
+            className={`tab-button ${activeTab === "employees" ? "active" : ""}`}
+            onClick={() => setActiveTab("employees")}
+          >
+            <FiUsers size={18} /> Empleados
+          </button>
+        )}
+        {can("manage_users") && (
+          <button
+            className={`tab-button ${activeTab === "users" ? "active" : ""}`}
+            onClick={() => setActiveTab("users")}
+          >
+            <FiShield size={18} /> Usuarios
+          </button>
+        )}
+        {can("view_audit") && (
+          <button
+            className={`tab-button ${activeTab === "audit" ? "active" : ""}`}
+            onClick={() => setActiveTab("audit")}
+          >
+            <FiFileText size={18} /> Bitácora
+          </button>
+        )}
+        {can("view_audit") && (
+          <button
+            className={`tab-button ${activeTab === "clock" ? "active" : ""}`}
+            onClick={() => setActiveTab("clock")}
+          >
+            <FiWatch size={18} /> Reloj
+          </button>
+        )}
+        {(can("delete_records") || can("manage_employees")) && (
+          <button
+            className={`tab-button ${activeTab === "trash" ? "active" : ""}`}
+            onClick={() => setActiveTab("trash")}
+          >
+            <FiArchive size={18} /> Papelera
+          </button>
+        )}
+        <button
+          className={`tab-button ${activeTab === "settings" ? "active" : ""}`}
+          onClick={() => setActiveTab("settings")}
         >
-          <FiUsers size={18} /> Empleados
+          <FiSettings size={18} /> Configuración
         </button>
       </div>
 
       {/* Content based on active tab */}
       {activeTab === "employees" ? (
-        <EmployeeManagement />
+        <EmployeeManagement token={token} />
+      ) : activeTab === "timesheet" ? (
+        <TimesheetView token={token} />
+      ) : activeTab === "tardiness" ? (
+        <TardinessReport token={token} />
+      ) : activeTab === "absences" ? (
+        <AbsenceReport token={token} />
+      ) : activeTab === "users" ? (
+        <UserManagement token={token} />
+      ) : activeTab === "audit" ? (
+        <AuditLog token={token} />
+      ) : activeTab === "clock" ? (
+        <ClockAnomalyReport token={token} />
+      ) : activeTab === "trash" ? (
+        <RecycleBinView
+          token={token}
+          canManageRecords={can("delete_records")}
+          canManageEmployees={can("manage_employees")}
+        />
+      ) : activeTab === "settings" ? (
+        <SettingsPanel token={token} canManage={can("manage_settings")} onSessionEnded={onBack} />
       ) : (
         <>
           {/* Messages */}
@@ -202,6 +316,17 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
                 <span className="stats-label">Salidas Hoy</span>
                 <span className="stats-value">{stats.total_exits}</span>
               </div>
+              <div className="stats-card">
+                <span className="stats-label">Ausentes Hoy</span>
+                <span
+                  className="stats-value"
+                  title={stats.absentees
+                    .map((a) => `${a.employee_name} (${a.expected_start})`)
+                    .join("\n")}
+                >
+                  {stats.absentees.length}
+                </span>
+              </div>
               <div className="stats-card">
                 <span className="stats-label">Ultima Actividad</span>
                 <span className="stats-value">
@@ -270,6 +395,40 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
                 <option value="exit">Salida</option>
               </select>
             </div>
+            <div className="filter-group">
+              <label htmlFor="filter-source">Origen</label>
+              <select
+                id="filter-source"
+                className="filter-input"
+                value={filters.source || ""}
+                onChange={(e) =>
+                  setFilters((f) => ({
+                    ...f,
+                    source: (e.target.value as RecordSource) || undefined,
+                  }))
+                }
+              >
+                <option value="">Todos</option>
+                {(Object.keys(SOURCE_LABELS) as RecordSource[]).map((source) => (
+                  <option key={source} value={source}>
+                    {SOURCE_LABELS[source]}
+                  </option>
+                ))}
+              </select>
+            </div>
+            <div className="filter-group">
+              <label htmlFor="filter-device">Dispositivo</label>
+              <input
+                id="filter-device"
+                type="text"
+                className="filter-input"
+                placeholder="Todos"
+                value={filters.device_id || ""}
+                onChange={(e) =>
+                  setFilters((f) => ({ ...f, device_id: e.target.value || undefined }))
+                }
+              />
+            </div>
             <div className="filter-actions">
               <button className="btn-search" onClick={handleSearch}>
                 Buscar
@@ -277,9 +436,16 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
               <button className="btn-refresh" onClick={handleRefresh} title="Recargar">
                 <FiRefreshCw size={16} /> Recargar
               </button>
-              <button className="btn-export" onClick={handleExport} title="Exportar Excel">
-                <FiDownload size={16} /> Exportar Excel
-              </button>
+              {can("edit_records") && (
+                <button className="btn-add" onClick={() => setShowManualRecord(true)} title="Agregar Registro">
+                  <FiPlus size={16} /> Agregar Registro
+                </button>
+              )}
+              {can("export_records") && (
+                <button className="btn-export" onClick={handleExport} title="Exportar Excel">
+                  <FiDownload size={16} /> Exportar Excel
+                </button>
+              )}
             </div>
           </section>
 
@@ -337,6 +503,13 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
                               value={editNotes}
                               onChange={(e) => setEditNotes(e.target.value)}
                             />
+                            <input
+                              type="text"
+                              className="edit-input"
+                              placeholder="Motivo de la corrección..."
+                              value={editReason}
+                              onChange={(e) => setEditReason(e.target.value)}
+                            />
                           </td>
                           <td className="actions-cell">
                             <button className="btn-save" onClick={saveEdit}>
@@ -358,23 +531,61 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
                             <span className={`type-badge type-${record.type}`}>
                               {record.type === "entry" ? "Entrada" : "Salida"}
                             </span>
+                            {record.late_minutes !== null && (
+                              <span className="deviation-badge" title="Retardo">
+                                +{record.late_minutes} min
+                              </span>
+                            )}
+                            {record.early_departure_minutes !== null && (
+                              <span className="deviation-badge" title="Salida anticipada">
+                                -{record.early_departure_minutes} min
+                              </span>
+                            )}
+                            {record.source !== "kiosk" && (
+                              <span
+                                className="flag-badge"
+                                title={`${SOURCE_LABELS[record.source] ?? record.source}${
+                                  record.device_id ? ` (${record.device_id})` : ""
+                                }`}
+                              >
+                                ✍️
+                              </span>
+                            )}
+                            {record.flag && (
+                              <span
+                                className="flag-badge"
+                                title={
+                                  record.flag === "placeholder"
+                                    ? "Generado automáticamente"
+                                    : record.flag === "clock_anomaly"
+                                      ? "Reloj del equipo alterado"
+                                      : "Fuera de secuencia"
+                                }
+                              >
+                                ⚠️
+                              </span>
+                            )}
                           </td>
                           <td>{record.notes || "-"}</td>
                           <td className="actions-cell">
-                            <button
-                              className="btn-edit"
-                              onClick={() => startEdit(record)}
-                              title="Editar"
-                            >
-                              <FiEdit2 size={16} />
-                            </button>
-                            <button
-                              className="btn-delete"
-                              onClick={() => setDeleteId(record.id)}
-                              title="Eliminar"
-                            >
-                              <FiTrash2 size={16} />
-                            </button>
+                            {can("edit_records") && (
+                              <button
+                                className="btn-edit"
+                                onClick={() => startEdit(record)}
+                                title="Editar"
+                              >
+                                <FiEdit2 size={16} />
+                              </button>
+                            )}
+                            {can("delete_records") && (
+                              <button
+                                className="btn-delete"
+                                onClick={() => setDeleteId(record.id)}
+                                title="Eliminar"
+                              >
+                                <FiTrash2 size={16} />
+                              </button>
+                            )}
                           </td>
                         </>
                       )}
@@ -385,13 +596,38 @@ export default function AdminPanel({ onBack }: AdminPanelProps) {
             )}
           </section>
 
+          {showManualRecord && (
+            <ManualRecordDialog
+              token={token}
+              onClose={() => setShowManualRecord(false)}
+              onSaved={(message) => {
+                setShowManualRecord(false);
+                setSuccessMessage(message);
+                loadRecords(filters);
+                loadStats();
+              }}
+            />
+          )}
+
           {/* Delete Confirmation */}
           <ConfirmDialog
             isOpen={deleteId !== null}
-            message="Esta seguro que desea eliminar este registro? Esta accion no se puede deshacer."
+            message="Esta seguro que desea eliminar este registro? Podra restaurarse desde la papelera."
             onConfirm={confirmDelete}
-            onCancel={() => setDeleteId(null)}
-          />
+            onCancel={() => {
+              setDeleteId(null);
+              setDeleteReason("");
+            }}
+          >
+            <input
+              type="text"
+              className="password-input"
+              placeholder="Motivo de la eliminación..."
+              value={deleteReason}
+              onChange={(e) => setDeleteReason(e.target.value)}
+              autoFocus
+            />
+          </ConfirmDialog>
         </>
       )}
     </div>
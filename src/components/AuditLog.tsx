@@ -0,0 +1,192 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiDownload } from "react-icons/fi";
+import type { AuditEntry } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+const COMMAND_LABELS: Record<string, string> = {
+    create_manual_record: "Registro manual",
+    update_record: "Edición de registro",
+    delete_record: "Eliminación de registro",
+    create_employee: "Alta de empleado",
+    update_employee: "Edición de empleado",
+    delete_employee: "Baja de empleado",
+    reset_employee_pin: "Cambio de PIN",
+    assign_badge: "Asignación de gafete",
+    revoke_badge: "Revocación de gafete",
+    restore_record: "Restauración de registro",
+    purge_record: "Eliminación definitiva de registro",
+    restore_employee: "Restauración de empleado",
+    purge_employee: "Eliminación definitiva de empleado",
+};
+
+// Fields that change on every update and carry no information on their own
+const IGNORED_FIELDS = ["updated_at"];
+
+const formatValue = (value: unknown) =>
+    value === null || value === undefined || value === "" ? "-" : String(value);
+
+const describeChanges = (entry: AuditEntry): string[] => {
+    if (!entry.before && entry.after) {
+        return ["Creado"];
+    }
+    if (entry.before && !entry.after) {
+        return ["Eliminado"];
+    }
+    const before = entry.before ?? {};
+    const after = entry.after ?? {};
+    return Object.keys(after)
+        .filter((key) => !IGNORED_FIELDS.includes(key))
+        .filter((key) => JSON.stringify(before[key]) !== JSON.stringify(after[key]))
+        .map((key) => `${key}: ${formatValue(before[key])} → ${formatValue(after[key])}`);
+};
+
+interface AuditLogProps {
+    token: string;
+}
+
+export default function AuditLog({ token }: AuditLogProps) {
+    const [recordId, setRecordId] = useState("");
+    const [employeeId, setEmployeeId] = useState("");
+    const [startDate, setStartDate] = useState("");
+    const [endDate, setEndDate] = useState("");
+    const [entries, setEntries] = useState<AuditEntry[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+
+    const filterParams = () => ({
+        token,
+        recordId: recordId ? Number(recordId) : null,
+        employeeId: employeeId || null,
+        startDate: startDate || null,
+        endDate: endDate || null,
+    });
+
+    const loadEntries = async () => {
+        setLoading(true);
+        setError(null);
+        setSuccessMessage(null);
+        try {
+            setEntries(await invoke<AuditEntry[]>("get_audit_log", filterParams()));
+        } catch (err) {
+            setError(`Error al cargar bitácora: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const handleExport = async () => {
+        setError(null);
+        setSuccessMessage(null);
+        try {
+            const filePath = await invoke<string>("export_audit_log", filterParams());
+            setSuccessMessage(`Archivo exportado: ${filePath}`);
+        } catch (err) {
+            setError(`Error al exportar: ${describeError(err)}`);
+        }
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Bitácora de Cambios</h2>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+            {successMessage && <div className="message message-success">{successMessage}</div>}
+
+            <section className="filters-section">
+                <div className="filter-group">
+                    <label htmlFor="audit-record">ID Registro</label>
+                    <input
+                        id="audit-record"
+                        type="number"
+                        className="filter-input"
+                        placeholder="Todos"
+                        value={recordId}
+                        onChange={(e) => setRecordId(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="audit-employee">ID Empleado</label>
+                    <input
+                        id="audit-employee"
+                        type="text"
+                        className="filter-input"
+                        placeholder="Todos"
+                        value={employeeId}
+                        onChange={(e) => setEmployeeId(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="audit-start">Fecha Inicio</label>
+                    <input
+                        id="audit-start"
+                        type="date"
+                        className="filter-input"
+                        value={startDate}
+                        onChange={(e) => setStartDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="audit-end">Fecha Fin</label>
+                    <input
+                        id="audit-end"
+                        type="date"
+                        className="filter-input"
+                        value={endDate}
+                        onChange={(e) => setEndDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-actions">
+                    <button className="btn-search" onClick={loadEntries}>
+                        Buscar
+                    </button>
+                    <button className="btn-export" onClick={handleExport} title="Exportar Excel">
+                        <FiDownload size={16} /> Exportar Excel
+                    </button>
+                </div>
+            </section>
+
+            <div className="employees-section">
+                {loading ? (
+                    <div className="loading">Cargando bitácora...</div>
+                ) : entries.length === 0 ? (
+                    <div className="no-records">Sin cambios registrados.</div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Fecha</th>
+                                <th>Usuario</th>
+                                <th>Acción</th>
+                                <th>Registro</th>
+                                <th>Empleado</th>
+                                <th>Cambios</th>
+                                <th>Motivo</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {entries.map((entry) => (
+                                <tr key={entry.id}>
+                                    <td>{entry.timestamp}</td>
+                                    <td>{entry.actor}</td>
+                                    <td>{COMMAND_LABELS[entry.command] ?? entry.command}</td>
+                                    <td>{entry.entity === "attendance" ? entry.entity_id : "-"}</td>
+                                    <td>{entry.employee_id}</td>
+                                    <td>
+                                        {describeChanges(entry).map((change) => (
+                                            <div key={change}>{change}</div>
+                                        ))}
+                                    </td>
+                                    <td>{entry.reason || "-"}</td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,183 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiCheck, FiDatabase, FiRotateCcw } from "react-icons/fi";
+import ConfirmDialog from "./ConfirmDialog";
+import type { BackupInfo, BackupKind, BackupSettings } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+const KIND_LABELS: Record<BackupKind, string> = {
+    startup: "Al iniciar",
+    daily: "Diario",
+    manual: "Manual",
+    pre_migration: "Antes de actualizar",
+    pre_bulk: "Antes de eliminar",
+    pre_restore: "Antes de restaurar",
+};
+
+interface BackupManagerProps {
+    token: string;
+    onRestored: () => void;
+}
+
+function formatSize(bytes: number) {
+    return bytes >= 1024 * 1024
+        ? `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+        : `${Math.ceil(bytes / 1024)} KB`;
+}
+
+export default function BackupManager({ token, onRestored }: BackupManagerProps) {
+    const [backups, setBackups] = useState<BackupInfo[]>([]);
+    const [settings, setSettings] = useState<BackupSettings>({ dir: "", keep_daily: 7, keep_weekly: 4 });
+    const [restoreTarget, setRestoreTarget] = useState<BackupInfo | null>(null);
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+
+    const clearMessages = () => {
+        setError(null);
+        setSuccessMessage(null);
+    };
+
+    const loadBackups = async () => {
+        try {
+            setBackups(await invoke<BackupInfo[]>("get_backups", { token }));
+            setSettings(await invoke<BackupSettings>("get_backup_settings", { token }));
+        } catch (err) {
+            setError(`Error al cargar respaldos: ${describeError(err)}`);
+        }
+    };
+
+    useEffect(() => {
+        loadBackups();
+    }, []);
+
+    const handleSaveSettings = async () => {
+        clearMessages();
+        try {
+            setSuccessMessage(await invoke<string>("set_backup_settings", { token, settings }));
+            loadBackups();
+        } catch (err) {
+            setError(`Error al guardar configuración: ${describeError(err)}`);
+        }
+    };
+
+    const handleCreate = async () => {
+        clearMessages();
+        try {
+            const backup = await invoke<BackupInfo>("create_backup", { token });
+            setSuccessMessage(`Respaldo creado: ${backup.name}`);
+            loadBackups();
+        } catch (err) {
+            setError(`Error al crear respaldo: ${describeError(err)}`);
+        }
+    };
+
+    const confirmRestore = async () => {
+        if (!restoreTarget) return;
+        clearMessages();
+        try {
+            // Sessions end with the restore, so go back to the kiosk
+            await invoke<string>("restore_backup", { token, name: restoreTarget.name });
+            onRestored();
+        } catch (err) {
+            setError(`Error al restaurar respaldo: ${describeError(err)}`);
+        } finally {
+            setRestoreTarget(null);
+        }
+    };
+
+    return (
+        <>
+            <div className="employee-header">
+                <h2>Respaldos</h2>
+                <button className="btn-add" onClick={handleCreate}>
+                    <FiDatabase size={16} /> Crear Respaldo
+                </button>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+            {successMessage && <div className="message message-success">{successMessage}</div>}
+
+            <div className="add-employee-form">
+                <div className="form-row">
+                    <div className="form-group">
+                        <label htmlFor="backup-dir">Carpeta (vacío: junto a la base de datos)</label>
+                        <input
+                            id="backup-dir"
+                            type="text"
+                            className="form-input"
+                            value={settings.dir}
+                            onChange={(e) => setSettings((s) => ({ ...s, dir: e.target.value }))}
+                        />
+                    </div>
+                    <div className="form-group">
+                        <label htmlFor="backup-daily">Diarios a conservar</label>
+                        <input
+                            id="backup-daily"
+                            type="number"
+                            min={0}
+                            className="form-input"
+                            value={settings.keep_daily}
+                            onChange={(e) => setSettings((s) => ({ ...s, keep_daily: Number(e.target.value) }))}
+                        />
+                    </div>
+                    <div className="form-group">
+                        <label htmlFor="backup-weekly">Semanales a conservar</label>
+                        <input
+                            id="backup-weekly"
+                            type="number"
+                            min={0}
+                            className="form-input"
+                            value={settings.keep_weekly}
+                            onChange={(e) => setSettings((s) => ({ ...s, keep_weekly: Number(e.target.value) }))}
+                        />
+                    </div>
+                    <button className="btn-save" onClick={handleSaveSettings}>
+                        <FiCheck size={16} /> Guardar
+                    </button>
+                </div>
+            </div>
+
+            <div className="employees-section">
+                {backups.length === 0 ? (
+                    <div className="no-records">No hay respaldos.</div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Fecha</th>
+                                <th>Tipo</th>
+                                <th>Tamaño</th>
+                                <th>Acciones</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {backups.map((backup) => (
+                                <tr key={backup.name}>
+                                    <td title={backup.path}>{backup.created_at}</td>
+                                    <td>{KIND_LABELS[backup.kind] ?? backup.kind}</td>
+                                    <td>{formatSize(backup.size)}</td>
+                                    <td className="actions-cell">
+                                        <button
+                                            className="btn-edit"
+                                            onClick={() => setRestoreTarget(backup)}
+                                            title="Restaurar"
+                                        >
+                                            <FiRotateCcw size={16} />
+                                        </button>
+                                    </td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+
+            <ConfirmDialog
+                isOpen={restoreTarget !== null}
+                message={`¿Restaurar el respaldo del ${restoreTarget?.created_at}? Los cambios posteriores se perderán, aunque se guardará un respaldo del estado actual.`}
+                onConfirm={confirmRestore}
+                onCancel={() => setRestoreTarget(null)}
+            />
+        </>
+    );
+}
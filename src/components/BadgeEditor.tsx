@@ -0,0 +1,141 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiCheck, FiTrash2, FiX } from "react-icons/fi";
+import type { Badge, Employee } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface BadgeEditorProps {
+    token: string;
+    employee: Employee;
+    onClose: () => void;
+}
+
+export default function BadgeEditor({ token, employee, onClose }: BadgeEditorProps) {
+    const [badges, setBadges] = useState<Badge[]>([]);
+    const [code, setCode] = useState("");
+    const [label, setLabel] = useState("");
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+
+    const loadBadges = async () => {
+        try {
+            setBadges(await invoke<Badge[]>("get_badges", { token, employeeId: employee.id }));
+        } catch (err) {
+            setError(`Error al cargar gafetes: ${describeError(err)}`);
+        }
+    };
+
+    useEffect(() => {
+        loadBadges();
+    }, [employee.id]);
+
+    const handleAssign = async () => {
+        setError(null);
+        setSuccessMessage(null);
+        try {
+            setSuccessMessage(
+                await invoke<string>("assign_badge", {
+                    token,
+                    employeeId: employee.id,
+                    code,
+                    label: label || null,
+                })
+            );
+            setCode("");
+            setLabel("");
+            loadBadges();
+        } catch (err) {
+            setError(`Error al asignar gafete: ${describeError(err)}`);
+        }
+    };
+
+    const handleRevoke = async (badge: Badge) => {
+        setError(null);
+        setSuccessMessage(null);
+        try {
+            setSuccessMessage(await invoke<string>("revoke_badge", { token, code: badge.code }));
+            loadBadges();
+        } catch (err) {
+            setError(`Error al revocar gafete: ${describeError(err)}`);
+        }
+    };
+
+    return (
+        <div className="confirm-overlay" onClick={onClose}>
+            <div className="confirm-dialog schedule-dialog" onClick={(e) => e.stopPropagation()}>
+                <div className="employee-header">
+                    <h2>Gafetes de {employee.name}</h2>
+                    <button className="btn-close" onClick={onClose} title="Cerrar">
+                        <FiX size={20} />
+                    </button>
+                </div>
+
+                {error && <div className="message message-error">{error}</div>}
+                {successMessage && <div className="message message-success">{successMessage}</div>}
+
+                <div className="form-group">
+                    <label htmlFor="badge-code">Código (escanéalo o escríbelo)</label>
+                    <input
+                        id="badge-code"
+                        type="text"
+                        className="form-input"
+                        value={code}
+                        onChange={(e) => setCode(e.target.value)}
+                        onKeyDown={(e) => {
+                            if (e.key === "Enter") handleAssign();
+                        }}
+                        autoFocus
+                    />
+                </div>
+                <div className="form-group">
+                    <label htmlFor="badge-label">Descripción</label>
+                    <input
+                        id="badge-label"
+                        type="text"
+                        className="form-input"
+                        placeholder="Ej: Tarjeta, Llavero RFID"
+                        value={label}
+                        onChange={(e) => setLabel(e.target.value)}
+                    />
+                </div>
+
+                <div className="confirm-actions">
+                    <button className="btn-save" onClick={handleAssign}>
+                        <FiCheck size={16} /> Asignar
+                    </button>
+                </div>
+
+                {badges.length > 0 && (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Código</th>
+                                <th>Descripción</th>
+                                <th>Asignado</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {badges.map((badge) => (
+                                <tr key={badge.code}>
+                                    <td>{badge.code}</td>
+                                    <td>{badge.label || "-"}</td>
+                                    <td>{badge.created_at}</td>
+                                    <td className="actions-cell">
+                                        <button
+                                            className="btn-delete"
+                                            onClick={() => handleRevoke(badge)}
+                                            title="Revocar"
+                                        >
+                                            <FiTrash2 size={16} />
+                                        </button>
+                                    </td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,91 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiRefreshCw } from "react-icons/fi";
+import type { ClockAnomaly } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+const KIND_LABELS: Record<ClockAnomaly["kind"], string> = {
+    backward: "Atrasado",
+    forward: "Adelantado",
+};
+
+const formatDrift = (seconds: number) => {
+    const minutes = Math.round(Math.abs(seconds) / 60);
+    const hours = Math.floor(minutes / 60);
+    const sign = seconds < 0 ? "-" : "+";
+    return hours > 0 ? `${sign}${hours} h ${minutes % 60} min` : `${sign}${minutes} min`;
+};
+
+interface ClockAnomalyReportProps {
+    token: string;
+}
+
+export default function ClockAnomalyReport({ token }: ClockAnomalyReportProps) {
+    const [anomalies, setAnomalies] = useState<ClockAnomaly[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const loadAnomalies = async () => {
+        setLoading(true);
+        setError(null);
+        try {
+            setAnomalies(await invoke<ClockAnomaly[]>("get_clock_anomalies", { token }));
+        } catch (err) {
+            setError(`Error al cargar anomalías del reloj: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    useEffect(() => {
+        loadAnomalies();
+    }, []);
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Anomalías del Reloj</h2>
+                <button className="btn-refresh" onClick={loadAnomalies}>
+                    <FiRefreshCw size={16} /> Actualizar
+                </button>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+
+            <div className="employees-section">
+                {loading ? (
+                    <div className="loading">Cargando...</div>
+                ) : anomalies.length === 0 ? (
+                    <div className="no-records">
+                        Sin anomalías. Se detectan cuando la hora del equipo cambia entre registros.
+                    </div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Empleado</th>
+                                <th>Reloj</th>
+                                <th>Hora registrada</th>
+                                <th>Hora esperada</th>
+                                <th>Diferencia</th>
+                                <th>Registro</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {anomalies.map((anomaly) => (
+                                <tr key={anomaly.id}>
+                                    <td>{anomaly.employee_name || anomaly.employee_id}</td>
+                                    <td>{KIND_LABELS[anomaly.kind]}</td>
+                                    <td>{anomaly.detected_at}</td>
+                                    <td>{anomaly.expected_at}</td>
+                                    <td>{formatDrift(anomaly.drift_seconds)}</td>
+                                    <td>{anomaly.record_id ?? "Rechazado"}</td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
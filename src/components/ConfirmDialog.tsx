@@ -1,5 +1,8 @@
+import type { ReactNode } from "react";
+
 interface ConfirmDialogProps {
   message: string;
+  children?: ReactNode;
   onConfirm: () => void;
   onCancel: () => void;
   isOpen: boolean;
@@ -7,6 +10,7 @@ interface ConfirmDialogProps {
 
 export default function ConfirmDialog({
   message,
+  children,
   onConfirm,
   onCancel,
   isOpen,
@@ -17,6 +21,7 @@ export default function ConfirmDialog({
     <div className="confirm-overlay" onClick={onCancel}>
       <div className="confirm-dialog" onClick={(e) => e.stopPropagation()}>
         <p className="confirm-message">{message}</p>
+        {children}
         <div className="confirm-actions">
           <button className="btn-cancel" onClick={onCancel}>
             Cancelar
@@ -1,10 +1,17 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { FiEdit2, FiTrash2, FiPlus, FiX, FiCheck, FiToggleLeft, FiToggleRight } from "react-icons/fi";
+import { FiEdit2, FiTrash2, FiPlus, FiX, FiCheck, FiToggleLeft, FiToggleRight, FiCalendar, FiKey, FiCreditCard, FiPrinter } from "react-icons/fi";
 import ConfirmDialog from "./ConfirmDialog";
+import ScheduleEditor from "./ScheduleEditor";
+import BadgeEditor from "./BadgeEditor";
 import type { Employee } from "../types/attendance";
+import { describeError } from "../types/errors";
 
-export default function EmployeeManagement() {
+interface EmployeeManagementProps {
+    token: string;
+}
+
+export default function EmployeeManagement({ token }: EmployeeManagementProps) {
     const [employees, setEmployees] = useState<Employee[]>([]);
     const [loading, setLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
@@ -22,6 +29,20 @@ export default function EmployeeManagement() {
     // Delete confirmation state
     const [deleteId, setDeleteId] = useState<string | null>(null);
 
+    // Schedule editor state
+    const [scheduleEmployee, setScheduleEmployee] = useState<Employee | null>(null);
+
+    // Badge editor state
+    const [badgeEmployee, setBadgeEmployee] = useState<Employee | null>(null);
+
+    // PIN reset state
+    const [pinEmployee, setPinEmployee] = useState<Employee | null>(null);
+    const [newPin, setNewPin] = useState("");
+
+    // Badge sheet state
+    const [selectedIds, setSelectedIds] = useState<string[]>([]);
+    const [sheetPath, setSheetPath] = useState("");
+
     const clearMessages = () => {
         setError(null);
         setSuccessMessage(null);
@@ -36,7 +57,7 @@ export default function EmployeeManagement() {
             });
             setEmployees(result);
         } catch (err) {
-            setError(`Error al cargar empleados: ${err}`);
+            setError(`Error al cargar empleados: ${describeError(err)}`);
         } finally {
             setLoading(false);
         }
@@ -55,6 +76,7 @@ export default function EmployeeManagement() {
         clearMessages();
         try {
             await invoke("create_employee", {
+                token,
                 id: newId.trim(),
                 name: newName.trim(),
             });
@@ -64,7 +86,7 @@ export default function EmployeeManagement() {
             setNewName("");
             loadEmployees();
         } catch (err) {
-            setError(`Error al crear empleado: ${err}`);
+            setError(`Error al crear empleado: ${describeError(err)}`);
         }
     };
 
@@ -88,6 +110,7 @@ export default function EmployeeManagement() {
         clearMessages();
         try {
             await invoke("update_employee", {
+                token,
                 id: editingId,
                 name: editName.trim(),
                 active: null,
@@ -96,7 +119,7 @@ export default function EmployeeManagement() {
             cancelEdit();
             loadEmployees();
         } catch (err) {
-            setError(`Error al actualizar empleado: ${err}`);
+            setError(`Error al actualizar empleado: ${describeError(err)}`);
         }
     };
 
@@ -104,6 +127,7 @@ export default function EmployeeManagement() {
         clearMessages();
         try {
             await invoke("update_employee", {
+                token,
                 id: employee.id,
                 name: null,
                 active: !employee.active,
@@ -113,7 +137,51 @@ export default function EmployeeManagement() {
             );
             loadEmployees();
         } catch (err) {
-            setError(`Error al actualizar estado: ${err}`);
+            setError(`Error al actualizar estado: ${describeError(err)}`);
+        }
+    };
+
+    const closePinForm = () => {
+        setPinEmployee(null);
+        setNewPin("");
+    };
+
+    // An empty PIN removes it
+    const savePin = async (pin: string) => {
+        if (pinEmployee === null) return;
+        clearMessages();
+        try {
+            setSuccessMessage(
+                await invoke<string>("reset_employee_pin", {
+                    token,
+                    id: pinEmployee.id,
+                    pin: pin || null,
+                })
+            );
+            closePinForm();
+            loadEmployees();
+        } catch (err) {
+            setError(`Error al cambiar PIN: ${describeError(err)}`);
+        }
+    };
+
+    const toggleSelected = (id: string) => {
+        setSelectedIds((ids) => (ids.includes(id) ? ids.filter((i) => i !== id) : [...ids, id]));
+    };
+
+    const printBadges = async () => {
+        clearMessages();
+        try {
+            setSuccessMessage(
+                await invoke<string>("export_badge_sheet", {
+                    token,
+                    employeeIds: selectedIds,
+                    path: sheetPath || null,
+                })
+            );
+            setSelectedIds([]);
+        } catch (err) {
+            setError(`Error al imprimir gafetes: ${describeError(err)}`);
         }
     };
 
@@ -121,12 +189,12 @@ export default function EmployeeManagement() {
         if (deleteId === null) return;
         clearMessages();
         try {
-            await invoke("delete_employee", { id: deleteId });
+            await invoke("delete_employee", { token, id: deleteId });
             setSuccessMessage("Empleado eliminado exitosamente");
             setDeleteId(null);
             loadEmployees();
         } catch (err) {
-            setError(`Error al eliminar empleado: ${err}`);
+            setError(`Error al eliminar empleado: ${describeError(err)}`);
         }
     };
 
@@ -185,6 +253,70 @@ export default function EmployeeManagement() {
                 </div>
             )}
 
+            {/* PIN Reset Form */}
+            {pinEmployee && (
+                <div className="add-employee-form">
+                    <div className="form-row">
+                        <div className="form-group">
+                            <label htmlFor="employee-pin">
+                                Nuevo PIN de {pinEmployee.name} (4 a 8 dígitos)
+                            </label>
+                            <input
+                                id="employee-pin"
+                                type="password"
+                                inputMode="numeric"
+                                className="form-input"
+                                value={newPin}
+                                onChange={(e) => setNewPin(e.target.value)}
+                                onKeyDown={(e) => {
+                                    if (e.key === "Enter") savePin(newPin);
+                                    if (e.key === "Escape") closePinForm();
+                                }}
+                                autoFocus
+                            />
+                        </div>
+                        <button className="btn-save" onClick={() => savePin(newPin)}>
+                            <FiCheck size={16} /> Guardar
+                        </button>
+                        {pinEmployee.has_pin && (
+                            <button className="btn-delete" onClick={() => savePin("")}>
+                                Quitar PIN
+                            </button>
+                        )}
+                        <button className="btn-cancel-edit" onClick={closePinForm}>
+                            Cancelar
+                        </button>
+                    </div>
+                </div>
+            )}
+
+            {/* Badge Sheet Form */}
+            {selectedIds.length > 0 && (
+                <div className="add-employee-form">
+                    <div className="form-row">
+                        <div className="form-group">
+                            <label htmlFor="badge-sheet-path">
+                                Gafetes de {selectedIds.length} empleado(s), guardar en
+                            </label>
+                            <input
+                                id="badge-sheet-path"
+                                type="text"
+                                className="form-input"
+                                placeholder="Escritorio (predeterminado)"
+                                value={sheetPath}
+                                onChange={(e) => setSheetPath(e.target.value)}
+                            />
+                        </div>
+                        <button className="btn-save" onClick={printBadges}>
+                            <FiPrinter size={16} /> Imprimir gafetes
+                        </button>
+                        <button className="btn-cancel-edit" onClick={() => setSelectedIds([])}>
+                            Cancelar
+                        </button>
+                    </div>
+                </div>
+            )}
+
             {/* Employees Table */}
             <div className="employees-section">
                 {loading ? (
@@ -197,6 +329,7 @@ export default function EmployeeManagement() {
                     <table className="employee-table">
                         <thead>
                             <tr>
+                                <th></th>
                                 <th>ID</th>
                                 <th>Nombre</th>
                                 <th>Estado</th>
@@ -207,6 +340,14 @@ export default function EmployeeManagement() {
                         <tbody>
                             {employees.map((employee) => (
                                 <tr key={employee.id} className={!employee.active ? "inactive" : ""}>
+                                    <td>
+                                        <input
+                                            type="checkbox"
+                                            checked={selectedIds.includes(employee.id)}
+                                            onChange={() => toggleSelected(employee.id)}
+                                            title="Seleccionar para imprimir gafete"
+                                        />
+                                    </td>
                                     {editingId === employee.id ? (
                                         <>
                                             <td>{employee.id}</td>
@@ -264,6 +405,27 @@ export default function EmployeeManagement() {
                                                         <FiToggleLeft size={20} />
                                                     )}
                                                 </button>
+                                                <button
+                                                    className="btn-edit"
+                                                    onClick={() => setScheduleEmployee(employee)}
+                                                    title="Horario"
+                                                >
+                                                    <FiCalendar size={16} />
+                                                </button>
+                                                <button
+                                                    className="btn-edit"
+                                                    onClick={() => setBadgeEmployee(employee)}
+                                                    title="Gafetes"
+                                                >
+                                                    <FiCreditCard size={16} />
+                                                </button>
+                                                <button
+                                                    className="btn-edit"
+                                                    onClick={() => setPinEmployee(employee)}
+                                                    title={employee.has_pin ? "Cambiar PIN" : "Asignar PIN"}
+                                                >
+                                                    <FiKey size={16} />
+                                                </button>
                                                 <button
                                                     className="btn-edit"
                                                     onClick={() => startEdit(employee)}
@@ -288,10 +450,26 @@ export default function EmployeeManagement() {
                 )}
             </div>
 
+            {scheduleEmployee && (
+                <ScheduleEditor
+                    token={token}
+                    employee={scheduleEmployee}
+                    onClose={() => setScheduleEmployee(null)}
+                />
+            )}
+
+            {badgeEmployee && (
+                <BadgeEditor
+                    token={token}
+                    employee={badgeEmployee}
+                    onClose={() => setBadgeEmployee(null)}
+                />
+            )}
+
             {/* Delete Confirmation */}
             <ConfirmDialog
                 isOpen={deleteId !== null}
-                message={`¿Está seguro que desea eliminar este empleado? Esta acción no se puede deshacer.${employees.find((e) => e.id === deleteId)
+                message={`¿Está seguro que desea eliminar este empleado? Podrá restaurarse desde la papelera.${employees.find((e) => e.id === deleteId)
                         ? "\n\nNota: Los registros de asistencia existentes no se eliminarán."
                         : ""
                     }`}
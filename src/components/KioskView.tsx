@@ -1,10 +1,14 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import type { Employee } from '../types/attendance';
+import type { BadgePunch, Employee, PunchOutcome } from '../types/attendance';
+import { describeError } from '../types/errors';
 
 export default function KioskView() {
     const [employees, setEmployees] = useState<Employee[]>([]);
     const [selectedEmployeeId, setSelectedEmployeeId] = useState('');
+    const [pinRequired, setPinRequired] = useState(false);
+    const [pin, setPin] = useState('');
+    const [badgeCode, setBadgeCode] = useState('');
     const [lastRecord, setLastRecord] = useState('');
     const [loading, setLoading] = useState(true);
 
@@ -18,8 +22,9 @@ export default function KioskView() {
                 activeOnly: true,
             });
             setEmployees(result);
+            setPinRequired(await invoke<boolean>('get_pin_required'));
         } catch (error) {
-            setLastRecord(`❌ Error al cargar empleados: ${error}`);
+            setLastRecord(`❌ Error al cargar empleados: ${describeError(error)}`);
         } finally {
             setLoading(false);
         }
@@ -32,14 +37,17 @@ export default function KioskView() {
         }
         try {
             const employee = employees.find(e => e.id === selectedEmployeeId);
-            await invoke('check_in', {
+            const outcome = await invoke<PunchOutcome>('check_in', {
                 employeeId: selectedEmployeeId,
-                employeeName: employee?.name || null,
+                pin: pinRequired ? pin : null,
             });
-            setLastRecord(`✅ Entrada registrada: ${employee?.name || selectedEmployeeId}`);
+            const warning = outcome.warning ? ` ⚠️ ${outcome.warning}` : '';
+            setLastRecord(`✅ Entrada registrada: ${employee?.name || selectedEmployeeId}${warning}`);
             setSelectedEmployeeId('');
         } catch (error) {
-            setLastRecord(`❌ Error: ${error}`);
+            setLastRecord(`❌ Error: ${describeError(error)}`);
+        } finally {
+            setPin('');
         }
     };
 
@@ -50,14 +58,40 @@ export default function KioskView() {
         }
         try {
             const employee = employees.find(e => e.id === selectedEmployeeId);
-            await invoke('check_out', {
+            const outcome = await invoke<PunchOutcome>('check_out', {
                 employeeId: selectedEmployeeId,
-                employeeName: employee?.name || null,
+                pin: pinRequired ? pin : null,
             });
-            setLastRecord(`🔴 Salida registrada: ${employee?.name || selectedEmployeeId}`);
+            const warning = outcome.warning ? ` ⚠️ ${outcome.warning}` : '';
+            setLastRecord(`🔴 Salida registrada: ${employee?.name || selectedEmployeeId}${warning}`);
             setSelectedEmployeeId('');
         } catch (error) {
-            setLastRecord(`❌ Error: ${error}`);
+            setLastRecord(`❌ Error: ${describeError(error)}`);
+        } finally {
+            setPin('');
+        }
+    };
+
+    // Readers type the code and press Enter; with PINs the punch waits for it
+    const handleBadge = async () => {
+        if (!badgeCode.trim()) return;
+        if (pinRequired && !pin) {
+            setLastRecord('⚠️ Ingresa tu PIN y presiona Enter');
+            return;
+        }
+        try {
+            const outcome = await invoke<BadgePunch>('punch_by_badge', {
+                code: badgeCode,
+                pin: pinRequired ? pin : null,
+            });
+            const warning = outcome.warning ? ` ⚠️ ${outcome.warning}` : '';
+            const icon = outcome.state === 'present' ? '✅' : '🔴';
+            setLastRecord(`${icon} ${outcome.message}: ${outcome.employee_name}${warning}`);
+        } catch (error) {
+            setLastRecord(`❌ Error: ${describeError(error)}`);
+        } finally {
+            setBadgeCode('');
+            setPin('');
         }
     };
 
@@ -88,11 +122,22 @@ export default function KioskView() {
         <div className="kiosk">
             <h1>REGISTRO DE ASISTENCIA</h1>
 
+            <input
+                type="text"
+                className="employee-select"
+                placeholder="Escanea tu gafete"
+                value={badgeCode}
+                onChange={(e) => setBadgeCode(e.target.value)}
+                onKeyDown={(e) => {
+                    if (e.key === 'Enter') handleBadge();
+                }}
+                autoFocus
+            />
+
             <select
                 value={selectedEmployeeId}
                 onChange={(e) => setSelectedEmployeeId(e.target.value)}
                 className="employee-select"
-                autoFocus
             >
                 <option value="">-- Selecciona un empleado --</option>
                 {employees.map((employee) => (
@@ -102,6 +147,20 @@ export default function KioskView() {
                 ))}
             </select>
 
+            {pinRequired && (
+                <input
+                    type="password"
+                    inputMode="numeric"
+                    className="employee-select"
+                    placeholder="PIN"
+                    value={pin}
+                    onChange={(e) => setPin(e.target.value)}
+                    onKeyDown={(e) => {
+                        if (e.key === 'Enter' && badgeCode) handleBadge();
+                    }}
+                />
+            )}
+
             <div className="buttons">
                 <button onClick={handleCheckIn} className="btn-entry">
                     🟢 ENTRADA
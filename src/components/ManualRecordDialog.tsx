@@ -0,0 +1,117 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiCheck, FiX } from "react-icons/fi";
+import type { Employee } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface ManualRecordDialogProps {
+    token: string;
+    onClose: () => void;
+    onSaved: (message: string) => void;
+}
+
+export default function ManualRecordDialog({ token, onClose, onSaved }: ManualRecordDialogProps) {
+    const [employees, setEmployees] = useState<Employee[]>([]);
+    const [employeeId, setEmployeeId] = useState("");
+    const [recordType, setRecordType] = useState<"entry" | "exit">("entry");
+    const [timestamp, setTimestamp] = useState("");
+    const [notes, setNotes] = useState("");
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        invoke<Employee[]>("get_employees", { activeOnly: false })
+            .then(setEmployees)
+            .catch((err) => setError(`Error al cargar empleados: ${describeError(err)}`));
+    }, []);
+
+    const handleSave = async () => {
+        if (!employeeId || !timestamp) {
+            setError("Selecciona el empleado y la fecha y hora");
+            return;
+        }
+        setError(null);
+        try {
+            const message = await invoke<string>("create_manual_record", {
+                token,
+                employeeId,
+                recordType,
+                timestamp: timestamp.replace("T", " "),
+                notes: notes || null,
+            });
+            onSaved(message);
+        } catch (err) {
+            setError(describeError(err));
+        }
+    };
+
+    return (
+        <div className="confirm-overlay" onClick={onClose}>
+            <div className="confirm-dialog schedule-dialog" onClick={(e) => e.stopPropagation()}>
+                <div className="employee-header">
+                    <h2>Agregar Registro Manual</h2>
+                    <button className="btn-close" onClick={onClose} title="Cerrar">
+                        <FiX size={20} />
+                    </button>
+                </div>
+
+                {error && <div className="message message-error">{error}</div>}
+
+                <div className="form-group">
+                    <label htmlFor="manual-employee">Empleado</label>
+                    <select
+                        id="manual-employee"
+                        className="form-input"
+                        value={employeeId}
+                        onChange={(e) => setEmployeeId(e.target.value)}
+                    >
+                        <option value="">Selecciona...</option>
+                        {employees.map((employee) => (
+                            <option key={employee.id} value={employee.id}>
+                                {employee.id} - {employee.name}
+                            </option>
+                        ))}
+                    </select>
+                </div>
+                <div className="form-group">
+                    <label htmlFor="manual-type">Tipo</label>
+                    <select
+                        id="manual-type"
+                        className="form-input"
+                        value={recordType}
+                        onChange={(e) => setRecordType(e.target.value as "entry" | "exit")}
+                    >
+                        <option value="entry">Entrada</option>
+                        <option value="exit">Salida</option>
+                    </select>
+                </div>
+                <div className="form-group">
+                    <label htmlFor="manual-timestamp">Fecha y hora</label>
+                    <input
+                        id="manual-timestamp"
+                        type="datetime-local"
+                        className="form-input"
+                        value={timestamp}
+                        onChange={(e) => setTimestamp(e.target.value)}
+                    />
+                </div>
+                <div className="form-group">
+                    <label htmlFor="manual-notes">Notas</label>
+                    <input
+                        id="manual-notes"
+                        type="text"
+                        className="form-input"
+                        placeholder="Ej: Olvidó su gafete"
+                        value={notes}
+                        onChange={(e) => setNotes(e.target.value)}
+                    />
+                </div>
+
+                <div className="confirm-actions">
+                    <button className="btn-save" onClick={handleSave}>
+                        <FiCheck size={16} /> Guardar
+                    </button>
+                </div>
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,87 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { RecoveryStatus } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface RecoveryScreenProps {
+  status: RecoveryStatus;
+}
+
+export default function RecoveryScreen({ status }: RecoveryScreenProps) {
+  const [username, setUsername] = useState("");
+  const [password, setPassword] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [successMessage, setSuccessMessage] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const run = async (command: "restore_latest_backup" | "export_salvage") => {
+    setError(null);
+    setSuccessMessage(null);
+    setBusy(true);
+    try {
+      // A successful restore restarts the app and never returns
+      setSuccessMessage(await invoke<string>(command, { username, password }));
+    } catch (err) {
+      setError(describeError(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="kiosk recovery">
+      <h1>Modo de recuperación</h1>
+      <p>
+        Se encontraron problemas en la base de datos y la aplicación no puede iniciar
+        normalmente.
+      </p>
+
+      <ul className="recovery-problems">
+        {status.problems.map((problem, i) => (
+          <li key={i}>{problem}</li>
+        ))}
+      </ul>
+
+      <p className="settings-hint">Base de datos: {status.db_path}</p>
+      <p className="settings-hint">
+        Último respaldo: {status.latest_backup ?? "ninguno"}
+      </p>
+      <p className="settings-hint">
+        {status.has_recovery_secret
+          ? "Si las cuentas no se pueden leer, deja el usuario vacío y escribe la clave de recuperación o la clave de cifrado."
+          : "Si las cuentas no se pueden leer, deja el usuario vacío y escribe la clave de cifrado."}
+      </p>
+
+      <input
+        type="text"
+        className="password-input"
+        value={username}
+        onChange={(e) => setUsername(e.target.value)}
+        placeholder="Usuario (vacío para usar la clave de recuperación)..."
+      />
+      <input
+        type="password"
+        className="password-input"
+        value={password}
+        onChange={(e) => setPassword(e.target.value)}
+        placeholder="Contraseña..."
+      />
+
+      {error && <p className="auth-error">{error}</p>}
+      {successMessage && <div className="message message-success">{successMessage}</div>}
+
+      <div className="confirm-actions">
+        <button
+          className="btn-confirm"
+          onClick={() => run("restore_latest_backup")}
+          disabled={busy || !status.latest_backup}
+        >
+          Restaurar último respaldo
+        </button>
+        <button className="btn-cancel" onClick={() => run("export_salvage")} disabled={busy}>
+          Exportar datos recuperables
+        </button>
+      </div>
+    </div>
+  );
+}
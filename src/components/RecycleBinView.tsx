@@ -0,0 +1,184 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiRotateCcw, FiTrash2 } from "react-icons/fi";
+import ConfirmDialog from "./ConfirmDialog";
+import type { RecycleBin } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface RecycleBinViewProps {
+    token: string;
+    canManageRecords: boolean;
+    canManageEmployees: boolean;
+}
+
+type PurgeTarget = { kind: "record"; id: number } | { kind: "employee"; id: string };
+
+export default function RecycleBinView({ token, canManageRecords, canManageEmployees }: RecycleBinViewProps) {
+    const [bin, setBin] = useState<RecycleBin>({ records: [], employees: [] });
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+    const [purgeTarget, setPurgeTarget] = useState<PurgeTarget | null>(null);
+
+    const loadBin = async () => {
+        setLoading(true);
+        try {
+            setBin(await invoke<RecycleBin>("get_recycle_bin", { token }));
+        } catch (err) {
+            setError(`Error al cargar papelera: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    useEffect(() => {
+        loadBin();
+    }, []);
+
+    const run = async (command: string, id: number | string) => {
+        setError(null);
+        setSuccessMessage(null);
+        try {
+            setSuccessMessage(await invoke<string>(command, { token, id }));
+            loadBin();
+        } catch (err) {
+            setError(describeError(err));
+        }
+    };
+
+    const confirmPurge = async () => {
+        if (!purgeTarget) return;
+        await run(purgeTarget.kind === "record" ? "purge_record" : "purge_employee", purgeTarget.id);
+        setPurgeTarget(null);
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Papelera</h2>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+            {successMessage && <div className="message message-success">{successMessage}</div>}
+
+            {loading ? (
+                <div className="loading">Cargando papelera...</div>
+            ) : (
+                <>
+                    <div className="employee-header">
+                        <h2>Registros</h2>
+                    </div>
+                    <div className="employees-section">
+                        {bin.records.length === 0 ? (
+                            <div className="no-records">No hay registros eliminados.</div>
+                        ) : (
+                            <table className="employee-table">
+                                <thead>
+                                    <tr>
+                                        <th>ID</th>
+                                        <th>Empleado</th>
+                                        <th>Fecha y hora</th>
+                                        <th>Tipo</th>
+                                        <th>Eliminado</th>
+                                        <th>Por</th>
+                                        <th>Acciones</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {bin.records.map((record) => (
+                                        <tr key={record.id}>
+                                            <td>{record.id}</td>
+                                            <td>{record.employee_name || record.employee_id}</td>
+                                            <td>{record.timestamp}</td>
+                                            <td>{record.type === "entry" ? "Entrada" : "Salida"}</td>
+                                            <td>{record.deleted_at}</td>
+                                            <td>{record.deleted_by}</td>
+                                            <td className="actions-cell">
+                                                {canManageRecords && (
+                                                    <>
+                                                        <button
+                                                            className="btn-edit"
+                                                            onClick={() => run("restore_record", record.id)}
+                                                            title="Restaurar"
+                                                        >
+                                                            <FiRotateCcw size={16} />
+                                                        </button>
+                                                        <button
+                                                            className="btn-delete"
+                                                            onClick={() => setPurgeTarget({ kind: "record", id: record.id })}
+                                                            title="Eliminar definitivamente"
+                                                        >
+                                                            <FiTrash2 size={16} />
+                                                        </button>
+                                                    </>
+                                                )}
+                                            </td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                        )}
+                    </div>
+
+                    <div className="employee-header">
+                        <h2>Empleados</h2>
+                    </div>
+                    <div className="employees-section">
+                        {bin.employees.length === 0 ? (
+                            <div className="no-records">No hay empleados eliminados.</div>
+                        ) : (
+                            <table className="employee-table">
+                                <thead>
+                                    <tr>
+                                        <th>ID</th>
+                                        <th>Nombre</th>
+                                        <th>Eliminado</th>
+                                        <th>Por</th>
+                                        <th>Acciones</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {bin.employees.map((employee) => (
+                                        <tr key={employee.id}>
+                                            <td>{employee.id}</td>
+                                            <td>{employee.name}</td>
+                                            <td>{employee.deleted_at}</td>
+                                            <td>{employee.deleted_by}</td>
+                                            <td className="actions-cell">
+                                                {canManageEmployees && (
+                                                    <>
+                                                        <button
+                                                            className="btn-edit"
+                                                            onClick={() => run("restore_employee", employee.id)}
+                                                            title="Restaurar"
+                                                        >
+                                                            <FiRotateCcw size={16} />
+                                                        </button>
+                                                        <button
+                                                            className="btn-delete"
+                                                            onClick={() => setPurgeTarget({ kind: "employee", id: employee.id })}
+                                                            title="Eliminar definitivamente"
+                                                        >
+                                                            <FiTrash2 size={16} />
+                                                        </button>
+                                                    </>
+                                                )}
+                                            </td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                        )}
+                    </div>
+                </>
+            )}
+
+            <ConfirmDialog
+                isOpen={purgeTarget !== null}
+                message="¿Eliminar definitivamente? Esta acción no se puede deshacer."
+                onConfirm={confirmPurge}
+                onCancel={() => setPurgeTarget(null)}
+            />
+        </div>
+    );
+}
@@ -0,0 +1,199 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiCheck, FiTrash2, FiX } from "react-icons/fi";
+import type { Employee, ScheduleDay, WeeklySchedule } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+const WEEKDAYS = ["Lunes", "Martes", "Miércoles", "Jueves", "Viernes", "Sábado", "Domingo"];
+
+interface DayForm {
+    enabled: boolean;
+    start_time: string;
+    end_time: string;
+}
+
+const emptyWeek = (): DayForm[] =>
+    WEEKDAYS.map((_, i) => ({ enabled: i < 5, start_time: "09:00", end_time: "18:00" }));
+
+interface ScheduleEditorProps {
+    token: string;
+    employee: Employee;
+    onClose: () => void;
+}
+
+export default function ScheduleEditor({ token, employee, onClose }: ScheduleEditorProps) {
+    const [schedules, setSchedules] = useState<WeeklySchedule[]>([]);
+    const [effectiveFrom, setEffectiveFrom] = useState(new Date().toISOString().slice(0, 10));
+    const [week, setWeek] = useState<DayForm[]>(emptyWeek());
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+
+    const loadSchedules = async () => {
+        try {
+            const result = await invoke<WeeklySchedule[]>("get_employee_schedules", {
+                token,
+                employeeId: employee.id,
+            });
+            setSchedules(result);
+            // Start editing from the most recent schedule
+            if (result.length > 0) {
+                setWeek(
+                    WEEKDAYS.map((_, i) => {
+                        const day = result[0].days.find((d) => d.weekday === i);
+                        return day
+                            ? { enabled: true, start_time: day.start_time, end_time: day.end_time }
+                            : { enabled: false, start_time: "09:00", end_time: "18:00" };
+                    })
+                );
+            }
+        } catch (err) {
+            setError(`Error al cargar horarios: ${describeError(err)}`);
+        }
+    };
+
+    useEffect(() => {
+        loadSchedules();
+    }, [employee.id]);
+
+    const updateDay = (index: number, changes: Partial<DayForm>) => {
+        setWeek((w) => w.map((d, i) => (i === index ? { ...d, ...changes } : d)));
+    };
+
+    const handleSave = async () => {
+        setError(null);
+        setSuccessMessage(null);
+        const days: ScheduleDay[] = week.flatMap((d, i) =>
+            d.enabled ? [{ weekday: i, start_time: d.start_time, end_time: d.end_time }] : []
+        );
+        try {
+            await invoke("set_employee_schedule", {
+                token,
+                employeeId: employee.id,
+                effectiveFrom,
+                days,
+            });
+            setSuccessMessage("Horario guardado");
+            loadSchedules();
+        } catch (err) {
+            setError(`Error al guardar horario: ${describeError(err)}`);
+        }
+    };
+
+    const handleDelete = async (schedule: WeeklySchedule) => {
+        setError(null);
+        setSuccessMessage(null);
+        try {
+            await invoke("delete_employee_schedule", {
+                token,
+                employeeId: employee.id,
+                effectiveFrom: schedule.effective_from,
+            });
+            loadSchedules();
+        } catch (err) {
+            setError(`Error al eliminar horario: ${describeError(err)}`);
+        }
+    };
+
+    return (
+        <div className="confirm-overlay" onClick={onClose}>
+            <div className="confirm-dialog schedule-dialog" onClick={(e) => e.stopPropagation()}>
+                <div className="employee-header">
+                    <h2>Horario de {employee.name}</h2>
+                    <button className="btn-close" onClick={onClose} title="Cerrar">
+                        <FiX size={20} />
+                    </button>
+                </div>
+
+                {error && <div className="message message-error">{error}</div>}
+                {successMessage && <div className="message message-success">{successMessage}</div>}
+
+                <div className="form-group">
+                    <label htmlFor="schedule-effective">Vigente desde</label>
+                    <input
+                        id="schedule-effective"
+                        type="date"
+                        className="form-input"
+                        value={effectiveFrom}
+                        onChange={(e) => setEffectiveFrom(e.target.value)}
+                    />
+                </div>
+
+                <table className="employee-table">
+                    <tbody>
+                        {week.map((day, i) => (
+                            <tr key={WEEKDAYS[i]} className={day.enabled ? "" : "inactive"}>
+                                <td>
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            checked={day.enabled}
+                                            onChange={(e) => updateDay(i, { enabled: e.target.checked })}
+                                        />{" "}
+                                        {WEEKDAYS[i]}
+                                    </label>
+                                </td>
+                                <td>
+                                    <input
+                                        type="time"
+                                        className="edit-input"
+                                        value={day.start_time}
+                                        disabled={!day.enabled}
+                                        onChange={(e) => updateDay(i, { start_time: e.target.value })}
+                                    />
+                                </td>
+                                <td>
+                                    <input
+                                        type="time"
+                                        className="edit-input"
+                                        value={day.end_time}
+                                        disabled={!day.enabled}
+                                        onChange={(e) => updateDay(i, { end_time: e.target.value })}
+                                    />
+                                </td>
+                            </tr>
+                        ))}
+                    </tbody>
+                </table>
+
+                <div className="confirm-actions">
+                    <button className="btn-save" onClick={handleSave}>
+                        <FiCheck size={16} /> Guardar
+                    </button>
+                </div>
+
+                {schedules.length > 0 && (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Vigente desde</th>
+                                <th>Días</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {schedules.map((schedule) => (
+                                <tr key={schedule.effective_from}>
+                                    <td>{schedule.effective_from}</td>
+                                    <td>
+                                        {schedule.days
+                                            .map((d) => `${WEEKDAYS[d.weekday].slice(0, 3)} ${d.start_time}-${d.end_time}`)
+                                            .join(", ") || "Sin días laborales"}
+                                    </td>
+                                    <td className="actions-cell">
+                                        <button
+                                            className="btn-delete"
+                                            onClick={() => handleDelete(schedule)}
+                                            title="Eliminar"
+                                        >
+                                            <FiTrash2 size={16} />
+                                        </button>
+                                    </td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
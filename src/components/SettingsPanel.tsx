@@ -0,0 +1,453 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiCheck, FiPlus, FiTrash2 } from "react-icons/fi";
+import BackupManager from "./BackupManager";
+import type { Holiday, Locale, RecoveryStatus, SequencePolicy } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface SettingsPanelProps {
+    token: string;
+    canManage: boolean;
+    onSessionEnded: () => void;
+}
+
+export default function SettingsPanel({ token, canManage, onSessionEnded }: SettingsPanelProps) {
+    const [policy, setPolicy] = useState<SequencePolicy>("reject");
+    const [workdayStart, setWorkdayStart] = useState("00:00");
+    const [graceMinutes, setGraceMinutes] = useState(5);
+    const [deviceId, setDeviceId] = useState("");
+    const [locale, setLocale] = useState<Locale>("es-MX");
+    const [timezone, setTimezone] = useState("");
+    const [timezones, setTimezones] = useState<string[]>([]);
+    const [pinRequired, setPinRequired] = useState(false);
+    const [databasePath, setDatabasePath] = useState("");
+    const [encrypted, setEncrypted] = useState(false);
+    const [encryptionSecret, setEncryptionSecret] = useState("");
+    const [confirmEncryptionSecret, setConfirmEncryptionSecret] = useState("");
+    const [recoverySecret, setRecoverySecret] = useState("");
+    const [confirmRecoverySecret, setConfirmRecoverySecret] = useState("");
+    const [hasRecoverySecret, setHasRecoverySecret] = useState(false);
+    const [holidays, setHolidays] = useState<Holiday[]>([]);
+    const [holidayDate, setHolidayDate] = useState("");
+    const [holidayName, setHolidayName] = useState("");
+    const [currentPassword, setCurrentPassword] = useState("");
+    const [newPassword, setNewPassword] = useState("");
+    const [confirmPassword, setConfirmPassword] = useState("");
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+
+    const clearMessages = () => {
+        setError(null);
+        setSuccessMessage(null);
+    };
+
+    const loadSettings = async () => {
+        clearMessages();
+        try {
+            setPolicy(await invoke<SequencePolicy>("get_sequence_policy", { token }));
+            setWorkdayStart(await invoke<string>("get_workday_start", { token }));
+            setGraceMinutes(await invoke<number>("get_grace_minutes", { token }));
+            setDeviceId(await invoke<string>("get_device_id", { token }));
+            setLocale(await invoke<Locale>("get_locale", { token }));
+            setTimezone(await invoke<string>("get_timezone", { token }));
+            setTimezones(await invoke<string[]>("get_timezones"));
+            setPinRequired(await invoke<boolean>("get_pin_required"));
+            setDatabasePath(await invoke<string>("get_database_path", { token }));
+            setEncrypted(await invoke<boolean>("get_encryption_status", { token }));
+            setHasRecoverySecret(
+                (await invoke<RecoveryStatus>("get_recovery_status")).has_recovery_secret
+            );
+            setHolidays(await invoke<Holiday[]>("get_holidays", { token }));
+        } catch (err) {
+            setError(`Error al cargar configuración: ${describeError(err)}`);
+        }
+    };
+
+    useEffect(() => {
+        if (canManage) loadSettings();
+    }, [canManage]);
+
+    const handleSave = async () => {
+        clearMessages();
+        try {
+            await invoke("set_sequence_policy", { token, policy });
+            await invoke("set_workday_start", { token, startTime: workdayStart });
+            await invoke("set_grace_minutes", { token, minutes: graceMinutes });
+            await invoke("set_device_id", { token, deviceId });
+            await invoke("set_timezone", { token, timezone });
+            await invoke("set_pin_required", { token, required: pinRequired });
+            // Confirmed in the language just selected
+            setSuccessMessage(await invoke<string>("set_locale", { token, locale }));
+        } catch (err) {
+            setError(`Error al guardar configuración: ${describeError(err)}`);
+        }
+    };
+
+    const handleEncrypt = async () => {
+        clearMessages();
+        if (encryptionSecret !== confirmEncryptionSecret) {
+            setError("Las claves no coinciden");
+            return;
+        }
+        try {
+            setSuccessMessage(await invoke<string>("encrypt_database", { token, secret: encryptionSecret }));
+            setEncrypted(true);
+            setEncryptionSecret("");
+            setConfirmEncryptionSecret("");
+        } catch (err) {
+            setError(`Error al cifrar la base de datos: ${describeError(err)}`);
+        }
+    };
+
+    const handleSetRecoverySecret = async () => {
+        clearMessages();
+        if (recoverySecret !== confirmRecoverySecret) {
+            setError("Las claves no coinciden");
+            return;
+        }
+        try {
+            setSuccessMessage(await invoke<string>("set_recovery_secret", { token, secret: recoverySecret }));
+            setHasRecoverySecret(true);
+            setRecoverySecret("");
+            setConfirmRecoverySecret("");
+        } catch (err) {
+            setError(`Error al guardar la clave de recuperación: ${describeError(err)}`);
+        }
+    };
+
+    const handleAddHoliday = async () => {
+        clearMessages();
+        try {
+            await invoke("add_holiday", { token, date: holidayDate, name: holidayName });
+            setHolidayDate("");
+            setHolidayName("");
+            setHolidays(await invoke<Holiday[]>("get_holidays", { token }));
+        } catch (err) {
+            setError(`Error al guardar día festivo: ${describeError(err)}`);
+        }
+    };
+
+    const handleDeleteHoliday = async (date: string) => {
+        clearMessages();
+        try {
+            await invoke("delete_holiday", { token, date });
+            setHolidays(await invoke<Holiday[]>("get_holidays", { token }));
+        } catch (err) {
+            setError(`Error al eliminar día festivo: ${describeError(err)}`);
+        }
+    };
+
+    const handleChangePassword = async () => {
+        clearMessages();
+        if (newPassword !== confirmPassword) {
+            setError("Las contraseñas no coinciden");
+            return;
+        }
+        try {
+            const message = await invoke<string>("change_admin_password", {
+                token,
+                currentPassword,
+                newPassword,
+            });
+            setCurrentPassword("");
+            setNewPassword("");
+            setConfirmPassword("");
+            setSuccessMessage(message);
+        } catch (err) {
+            setError(`Error al cambiar contraseña: ${describeError(err)}`);
+        }
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Configuración</h2>
+            </div>
+
+            {/* Messages */}
+            {error && <div className="message message-error">{error}</div>}
+            {successMessage && <div className="message message-success">{successMessage}</div>}
+
+            {canManage && (
+                <>
+                    <div className="add-employee-form">
+                        <div className="form-row">
+                            <div className="form-group">
+                                <label htmlFor="sequence-policy">Registros fuera de secuencia</label>
+                                <select
+                                    id="sequence-policy"
+                                    className="form-input"
+                                    value={policy}
+                                    onChange={(e) => setPolicy(e.target.value as SequencePolicy)}
+                                >
+                                    <option value="reject">Rechazar el registro</option>
+                                    <option value="warn">Registrar con advertencia</option>
+                                    <option value="placeholder">Generar registro faltante para revisión</option>
+                                </select>
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="workday-start">Inicio de jornada (turnos nocturnos)</label>
+                                <input
+                                    id="workday-start"
+                                    type="time"
+                                    className="form-input"
+                                    value={workdayStart}
+                                    onChange={(e) => setWorkdayStart(e.target.value)}
+                                />
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="grace-minutes">Tolerancia de retardo (min)</label>
+                                <input
+                                    id="grace-minutes"
+                                    type="number"
+                                    min={0}
+                                    max={240}
+                                    className="form-input"
+                                    value={graceMinutes}
+                                    onChange={(e) => setGraceMinutes(Number(e.target.value))}
+                                />
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="device-id">Identificador del dispositivo</label>
+                                <input
+                                    id="device-id"
+                                    type="text"
+                                    className="form-input"
+                                    value={deviceId}
+                                    onChange={(e) => setDeviceId(e.target.value)}
+                                />
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="pin-required">
+                                    <input
+                                        id="pin-required"
+                                        type="checkbox"
+                                        checked={pinRequired}
+                                        onChange={(e) => setPinRequired(e.target.checked)}
+                                    />{" "}
+                                    Pedir PIN del empleado en el kiosco
+                                </label>
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="timezone">Zona horaria del sitio</label>
+                                <input
+                                    id="timezone"
+                                    type="text"
+                                    list="timezone-options"
+                                    className="form-input"
+                                    value={timezone}
+                                    onChange={(e) => setTimezone(e.target.value)}
+                                />
+                                <datalist id="timezone-options">
+                                    {timezones.map((name) => (
+                                        <option key={name} value={name} />
+                                    ))}
+                                </datalist>
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="locale">Idioma de mensajes y exportaciones</label>
+                                <select
+                                    id="locale"
+                                    className="form-input"
+                                    value={locale}
+                                    onChange={(e) => setLocale(e.target.value as Locale)}
+                                >
+                                    <option value="es-MX">Español (México)</option>
+                                    <option value="en-US">English (US)</option>
+                                </select>
+                            </div>
+                            <button className="btn-save" onClick={handleSave}>
+                                <FiCheck size={16} /> Guardar
+                            </button>
+                        </div>
+                        <p className="settings-hint">Base de datos: {databasePath}</p>
+                    </div>
+
+                    <div className="employee-header">
+                        <h2>Días Festivos</h2>
+                    </div>
+
+                    <div className="add-employee-form">
+                        <div className="form-row">
+                            <div className="form-group">
+                                <label htmlFor="holiday-date">Fecha</label>
+                                <input
+                                    id="holiday-date"
+                                    type="date"
+                                    className="form-input"
+                                    value={holidayDate}
+                                    onChange={(e) => setHolidayDate(e.target.value)}
+                                />
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="holiday-name">Nombre</label>
+                                <input
+                                    id="holiday-name"
+                                    type="text"
+                                    className="form-input"
+                                    placeholder="Ej: Día de la Independencia"
+                                    value={holidayName}
+                                    onChange={(e) => setHolidayName(e.target.value)}
+                                />
+                            </div>
+                            <button className="btn-add" onClick={handleAddHoliday}>
+                                <FiPlus size={16} /> Agregar
+                            </button>
+                        </div>
+                    </div>
+
+                    <div className="employees-section">
+                        {holidays.length === 0 ? (
+                            <div className="no-records">No hay días festivos registrados.</div>
+                        ) : (
+                            <table className="employee-table">
+                                <tbody>
+                                    {holidays.map((holiday) => (
+                                        <tr key={holiday.date}>
+                                            <td>{holiday.date}</td>
+                                            <td>{holiday.name}</td>
+                                            <td className="actions-cell">
+                                                <button
+                                                    className="btn-delete"
+                                                    onClick={() => handleDeleteHoliday(holiday.date)}
+                                                    title="Eliminar"
+                                                >
+                                                    <FiTrash2 size={16} />
+                                                </button>
+                                            </td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                        )}
+                    </div>
+
+                    <div className="employee-header">
+                        <h2>Cifrado</h2>
+                    </div>
+
+                    {encrypted ? (
+                        <p className="settings-hint">
+                            La base de datos y sus respaldos están cifrados. La clave se pide al iniciar la
+                            aplicación.
+                        </p>
+                    ) : (
+                        <div className="add-employee-form">
+                            <div className="form-row">
+                                <div className="form-group">
+                                    <label htmlFor="encryption-secret">Clave de cifrado</label>
+                                    <input
+                                        id="encryption-secret"
+                                        type="password"
+                                        className="form-input"
+                                        value={encryptionSecret}
+                                        onChange={(e) => setEncryptionSecret(e.target.value)}
+                                    />
+                                </div>
+                                <div className="form-group">
+                                    <label htmlFor="confirm-encryption-secret">Confirmar clave</label>
+                                    <input
+                                        id="confirm-encryption-secret"
+                                        type="password"
+                                        className="form-input"
+                                        value={confirmEncryptionSecret}
+                                        onChange={(e) => setConfirmEncryptionSecret(e.target.value)}
+                                    />
+                                </div>
+                                <button className="btn-save" onClick={handleEncrypt}>
+                                    <FiCheck size={16} /> Cifrar
+                                </button>
+                            </div>
+                            <p className="settings-hint">
+                                Sin la clave no es posible recuperar los datos ni los respaldos. Guárdala en un
+                                lugar seguro.
+                            </p>
+                        </div>
+                    )}
+
+                    <div className="employee-header">
+                        <h2>Clave de recuperación</h2>
+                    </div>
+
+                    <div className="add-employee-form">
+                        <div className="form-row">
+                            <div className="form-group">
+                                <label htmlFor="recovery-secret">
+                                    {hasRecoverySecret ? "Nueva clave de recuperación" : "Clave de recuperación"}
+                                </label>
+                                <input
+                                    id="recovery-secret"
+                                    type="password"
+                                    className="form-input"
+                                    value={recoverySecret}
+                                    onChange={(e) => setRecoverySecret(e.target.value)}
+                                />
+                            </div>
+                            <div className="form-group">
+                                <label htmlFor="confirm-recovery-secret">Confirmar clave</label>
+                                <input
+                                    id="confirm-recovery-secret"
+                                    type="password"
+                                    className="form-input"
+                                    value={confirmRecoverySecret}
+                                    onChange={(e) => setConfirmRecoverySecret(e.target.value)}
+                                />
+                            </div>
+                            <button className="btn-save" onClick={handleSetRecoverySecret}>
+                                <FiCheck size={16} /> Guardar
+                            </button>
+                        </div>
+                        <p className="settings-hint">
+                            Permite restaurar respaldos desde el modo de recuperación cuando las cuentas de la
+                            base de datos no se pueden leer.
+                        </p>
+                    </div>
+
+                    <BackupManager token={token} onRestored={onSessionEnded} />
+                </>
+            )}
+
+            <div className="employee-header">
+                <h2>Cambiar Contraseña</h2>
+            </div>
+
+            <div className="add-employee-form">
+                <div className="form-row">
+                    <div className="form-group">
+                        <label htmlFor="current-password">Contraseña actual</label>
+                        <input
+                            id="current-password"
+                            type="password"
+                            className="form-input"
+                            value={currentPassword}
+                            onChange={(e) => setCurrentPassword(e.target.value)}
+                        />
+                    </div>
+                    <div className="form-group">
+                        <label htmlFor="new-password">Nueva contraseña</label>
+                        <input
+                            id="new-password"
+                            type="password"
+                            className="form-input"
+                            value={newPassword}
+                            onChange={(e) => setNewPassword(e.target.value)}
+                        />
+                    </div>
+                    <div className="form-group">
+                        <label htmlFor="confirm-password">Confirmar contraseña</label>
+                        <input
+                            id="confirm-password"
+                            type="password"
+                            className="form-input"
+                            value={confirmPassword}
+                            onChange={(e) => setConfirmPassword(e.target.value)}
+                        />
+                    </div>
+                    <button className="btn-save" onClick={handleChangePassword}>
+                        <FiCheck size={16} /> Cambiar
+                    </button>
+                </div>
+            </div>
+        </div>
+    );
+}
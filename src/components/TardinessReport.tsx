@@ -0,0 +1,133 @@
+import { Fragment, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { TardinessSummary } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface TardinessReportProps {
+    token: string;
+}
+
+export default function TardinessReport({ token }: TardinessReportProps) {
+    const [startDate, setStartDate] = useState("");
+    const [endDate, setEndDate] = useState("");
+    const [employeeId, setEmployeeId] = useState("");
+    const [report, setReport] = useState<TardinessSummary[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const loadReport = async () => {
+        setLoading(true);
+        setError(null);
+        try {
+            const result = await invoke<TardinessSummary[]>("get_tardiness_report", {
+                token,
+                startDate: startDate || null,
+                endDate: endDate || null,
+                employeeId: employeeId || null,
+            });
+            setReport(result);
+        } catch (err) {
+            setError(`Error al generar reporte: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Retardos y Salidas Anticipadas</h2>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+
+            <section className="filters-section">
+                <div className="filter-group">
+                    <label htmlFor="tardiness-start">Fecha Inicio</label>
+                    <input
+                        id="tardiness-start"
+                        type="date"
+                        className="filter-input"
+                        value={startDate}
+                        onChange={(e) => setStartDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="tardiness-end">Fecha Fin</label>
+                    <input
+                        id="tardiness-end"
+                        type="date"
+                        className="filter-input"
+                        value={endDate}
+                        onChange={(e) => setEndDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="tardiness-employee">ID Empleado</label>
+                    <input
+                        id="tardiness-employee"
+                        type="text"
+                        className="filter-input"
+                        placeholder="Todos"
+                        value={employeeId}
+                        onChange={(e) => setEmployeeId(e.target.value)}
+                    />
+                </div>
+                <div className="filter-actions">
+                    <button className="btn-search" onClick={loadReport}>
+                        Generar
+                    </button>
+                </div>
+            </section>
+
+            <div className="employees-section">
+                {loading ? (
+                    <div className="loading">Generando...</div>
+                ) : report.length === 0 ? (
+                    <div className="no-records">Sin incidencias en el periodo.</div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Empleado</th>
+                                <th>Jornada</th>
+                                <th>Incidencia</th>
+                                <th>Esperado</th>
+                                <th>Registrado</th>
+                                <th>Minutos</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {report.map((summary) => (
+                                <Fragment key={summary.employee_id}>
+                                    {summary.incidents.map((incident) => (
+                                        <tr key={`${incident.record_id}-${incident.kind}`}>
+                                            <td>{summary.employee_name || summary.employee_id}</td>
+                                            <td>{incident.workday}</td>
+                                            <td>{incident.kind === "late" ? "Retardo" : "Salida anticipada"}</td>
+                                            <td>{incident.expected}</td>
+                                            <td>{incident.actual}</td>
+                                            <td>{incident.minutes}</td>
+                                        </tr>
+                                    ))}
+                                    <tr>
+                                        <td>
+                                            <strong>{summary.employee_name || summary.employee_id}</strong>
+                                        </td>
+                                        <td colSpan={5}>
+                                            <strong>
+                                                {summary.late_count} retardos ({summary.late_minutes} min),{" "}
+                                                {summary.early_departure_count} salidas anticipadas (
+                                                {summary.early_departure_minutes} min)
+                                            </strong>
+                                        </td>
+                                    </tr>
+                                </Fragment>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,152 @@
+import { Fragment, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { EmployeeTimesheet } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+const formatDuration = (seconds: number) => {
+    const hours = Math.floor(seconds / 3600);
+    const minutes = Math.floor((seconds % 3600) / 60);
+    return `${hours}h ${minutes.toString().padStart(2, "0")}m`;
+};
+
+const unmatchedLabel = {
+    missing_exit: "Entrada sin salida",
+    missing_entry: "Salida sin entrada",
+    placeholder: "Registro generado, pendiente de revisión",
+    invalid_timestamp: "Fecha inválida",
+};
+
+interface TimesheetViewProps {
+    token: string;
+}
+
+export default function TimesheetView({ token }: TimesheetViewProps) {
+    const [startDate, setStartDate] = useState("");
+    const [endDate, setEndDate] = useState("");
+    const [employeeId, setEmployeeId] = useState("");
+    const [timesheets, setTimesheets] = useState<EmployeeTimesheet[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const loadTimesheet = async () => {
+        setLoading(true);
+        setError(null);
+        try {
+            const result = await invoke<EmployeeTimesheet[]>("get_timesheet", {
+                token,
+                startDate: startDate || null,
+                endDate: endDate || null,
+                employeeId: employeeId || null,
+            });
+            setTimesheets(result);
+        } catch (err) {
+            setError(`Error al calcular horas: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Horas Trabajadas</h2>
+            </div>
+
+            {error && <div className="message message-error">{error}</div>}
+
+            <section className="filters-section">
+                <div className="filter-group">
+                    <label htmlFor="timesheet-start">Fecha Inicio</label>
+                    <input
+                        id="timesheet-start"
+                        type="date"
+                        className="filter-input"
+                        value={startDate}
+                        onChange={(e) => setStartDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="timesheet-end">Fecha Fin</label>
+                    <input
+                        id="timesheet-end"
+                        type="date"
+                        className="filter-input"
+                        value={endDate}
+                        onChange={(e) => setEndDate(e.target.value)}
+                    />
+                </div>
+                <div className="filter-group">
+                    <label htmlFor="timesheet-employee">ID Empleado</label>
+                    <input
+                        id="timesheet-employee"
+                        type="text"
+                        className="filter-input"
+                        placeholder="Todos"
+                        value={employeeId}
+                        onChange={(e) => setEmployeeId(e.target.value)}
+                    />
+                </div>
+                <div className="filter-actions">
+                    <button className="btn-search" onClick={loadTimesheet}>
+                        Calcular
+                    </button>
+                </div>
+            </section>
+
+            <div className="employees-section">
+                {loading ? (
+                    <div className="loading">Calculando...</div>
+                ) : timesheets.length === 0 ? (
+                    <div className="no-records">Sin registros en el periodo.</div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Empleado</th>
+                                <th>Fecha</th>
+                                <th>Turnos</th>
+                                <th>Horas</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {timesheets.map((sheet) => (
+                                <Fragment key={sheet.employee_id}>
+                                    {sheet.days.map((day) => (
+                                        <tr key={`${sheet.employee_id}-${day.date}`}>
+                                            <td>{sheet.employee_name || sheet.employee_id}</td>
+                                            <td>{day.date}</td>
+                                            <td>
+                                                {day.shifts.length}
+                                                {day.shifts.some((shift) => shift.needs_review) &&
+                                                    " ⚠️ Revisar"}
+                                            </td>
+                                            <td>{formatDuration(day.worked_seconds)}</td>
+                                        </tr>
+                                    ))}
+                                    {sheet.unmatched.map((punch) => (
+                                        <tr key={`${sheet.employee_id}-u${punch.record_id}`} className="inactive">
+                                            <td>{sheet.employee_name || sheet.employee_id}</td>
+                                            <td>{punch.timestamp}</td>
+                                            <td colSpan={2}>⚠️ {unmatchedLabel[punch.reason]}</td>
+                                        </tr>
+                                    ))}
+                                    <tr>
+                                        <td>
+                                            <strong>{sheet.employee_name || sheet.employee_id}</strong>
+                                        </td>
+                                        <td colSpan={2}>
+                                            <strong>Total del periodo</strong>
+                                        </td>
+                                        <td>
+                                            <strong>{formatDuration(sheet.total_seconds)}</strong>
+                                        </td>
+                                    </tr>
+                                </Fragment>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
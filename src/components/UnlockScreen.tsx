@@ -0,0 +1,47 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { RecoveryStatus } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+interface UnlockScreenProps {
+  onUnlocked: (status: RecoveryStatus) => void;
+}
+
+export default function UnlockScreen({ onUnlocked }: UnlockScreenProps) {
+  const [secret, setSecret] = useState("");
+  const [error, setError] = useState<string | null>(null);
+
+  const handleUnlock = async () => {
+    if (!secret) return;
+    setError(null);
+    try {
+      onUnlocked(await invoke<RecoveryStatus>("unlock_database", { secret }));
+    } catch (err) {
+      setError(describeError(err));
+    }
+  };
+
+  return (
+    <div className="kiosk">
+      <h1>Base de datos cifrada</h1>
+      <p>Un administrador debe ingresar la clave de cifrado para iniciar.</p>
+      <input
+        type="password"
+        className="password-input"
+        value={secret}
+        onChange={(e) => setSecret(e.target.value)}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") handleUnlock();
+        }}
+        placeholder="Clave de cifrado..."
+        autoFocus
+      />
+      {error && <p className="auth-error">{error}</p>}
+      <div className="confirm-actions">
+        <button className="btn-confirm" onClick={handleUnlock}>
+          Desbloquear
+        </button>
+      </div>
+    </div>
+  );
+}
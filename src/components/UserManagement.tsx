@@ -0,0 +1,332 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FiEdit2, FiPlus, FiX, FiCheck, FiToggleLeft, FiToggleRight, FiKey } from "react-icons/fi";
+import type { Role, UserAccount } from "../types/attendance";
+import { describeError } from "../types/errors";
+
+export const ROLE_LABELS: Record<Role, string> = {
+    owner: "Propietario",
+    admin: "Administrador",
+    supervisor: "Supervisor",
+    auditor: "Auditor",
+};
+
+const ROLES = Object.keys(ROLE_LABELS) as Role[];
+
+interface UserManagementProps {
+    token: string;
+}
+
+export default function UserManagement({ token }: UserManagementProps) {
+    const [users, setUsers] = useState<UserAccount[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const [successMessage, setSuccessMessage] = useState<string | null>(null);
+
+    // Add user state
+    const [showAddForm, setShowAddForm] = useState(false);
+    const [newUsername, setNewUsername] = useState("");
+    const [newDisplayName, setNewDisplayName] = useState("");
+    const [newPassword, setNewPassword] = useState("");
+    const [newRole, setNewRole] = useState<Role>("supervisor");
+
+    // Edit user state
+    const [editingId, setEditingId] = useState<number | null>(null);
+    const [editDisplayName, setEditDisplayName] = useState("");
+    const [editRole, setEditRole] = useState<Role>("supervisor");
+
+    // Password reset state
+    const [resetId, setResetId] = useState<number | null>(null);
+    const [resetPassword, setResetPassword] = useState("");
+
+    const clearMessages = () => {
+        setError(null);
+        setSuccessMessage(null);
+    };
+
+    const loadUsers = async () => {
+        setLoading(true);
+        try {
+            setUsers(await invoke<UserAccount[]>("get_users", { token }));
+        } catch (err) {
+            setError(`Error al cargar usuarios: ${describeError(err)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    useEffect(() => {
+        loadUsers();
+    }, []);
+
+    const handleAddUser = async () => {
+        clearMessages();
+        try {
+            const message = await invoke<string>("create_user", {
+                token,
+                username: newUsername,
+                displayName: newDisplayName,
+                password: newPassword,
+                role: newRole,
+            });
+            setSuccessMessage(message);
+            setShowAddForm(false);
+            setNewUsername("");
+            setNewDisplayName("");
+            setNewPassword("");
+            loadUsers();
+        } catch (err) {
+            setError(`Error al crear usuario: ${describeError(err)}`);
+        }
+    };
+
+    const updateUser = async (user: UserAccount, displayName: string, role: Role, active: boolean) => {
+        clearMessages();
+        try {
+            const message = await invoke<string>("update_user", {
+                token,
+                id: user.id,
+                displayName,
+                role,
+                active,
+            });
+            setSuccessMessage(message);
+            setEditingId(null);
+            loadUsers();
+        } catch (err) {
+            setError(`Error al actualizar usuario: ${describeError(err)}`);
+        }
+    };
+
+    const startEdit = (user: UserAccount) => {
+        setEditingId(user.id);
+        setEditDisplayName(user.display_name);
+        setEditRole(user.role);
+    };
+
+    const handleResetPassword = async () => {
+        if (resetId === null) return;
+        clearMessages();
+        try {
+            const message = await invoke<string>("reset_user_password", {
+                token,
+                id: resetId,
+                newPassword: resetPassword,
+            });
+            setSuccessMessage(message);
+            setResetId(null);
+            setResetPassword("");
+        } catch (err) {
+            setError(`Error al restablecer contraseña: ${describeError(err)}`);
+        }
+    };
+
+    return (
+        <div className="employee-management">
+            <div className="employee-header">
+                <h2>Usuarios</h2>
+                <button
+                    className="btn-add"
+                    onClick={() => setShowAddForm(!showAddForm)}
+                    title={showAddForm ? "Cancelar" : "Agregar Usuario"}
+                >
+                    {showAddForm ? <FiX size={20} /> : <FiPlus size={20} />}
+                    {showAddForm ? " Cancelar" : " Agregar Usuario"}
+                </button>
+            </div>
+
+            {/* Messages */}
+            {error && <div className="message message-error">{error}</div>}
+            {successMessage && <div className="message message-success">{successMessage}</div>}
+
+            {/* Add User Form */}
+            {showAddForm && (
+                <div className="add-employee-form">
+                    <div className="form-row">
+                        <div className="form-group">
+                            <label htmlFor="new-username">Usuario</label>
+                            <input
+                                id="new-username"
+                                type="text"
+                                className="form-input"
+                                value={newUsername}
+                                onChange={(e) => setNewUsername(e.target.value)}
+                                autoFocus
+                            />
+                        </div>
+                        <div className="form-group">
+                            <label htmlFor="new-display-name">Nombre</label>
+                            <input
+                                id="new-display-name"
+                                type="text"
+                                className="form-input"
+                                value={newDisplayName}
+                                onChange={(e) => setNewDisplayName(e.target.value)}
+                            />
+                        </div>
+                        <div className="form-group">
+                            <label htmlFor="new-user-password">Contraseña</label>
+                            <input
+                                id="new-user-password"
+                                type="password"
+                                className="form-input"
+                                value={newPassword}
+                                onChange={(e) => setNewPassword(e.target.value)}
+                            />
+                        </div>
+                        <div className="form-group">
+                            <label htmlFor="new-user-role">Rol</label>
+                            <select
+                                id="new-user-role"
+                                className="form-input"
+                                value={newRole}
+                                onChange={(e) => setNewRole(e.target.value as Role)}
+                            >
+                                {ROLES.map((role) => (
+                                    <option key={role} value={role}>
+                                        {ROLE_LABELS[role]}
+                                    </option>
+                                ))}
+                            </select>
+                        </div>
+                        <button className="btn-save" onClick={handleAddUser}>
+                            <FiCheck size={16} /> Guardar
+                        </button>
+                    </div>
+                </div>
+            )}
+
+            {/* Password Reset Form */}
+            {resetId !== null && (
+                <div className="add-employee-form">
+                    <div className="form-row">
+                        <div className="form-group">
+                            <label htmlFor="reset-password">
+                                Nueva contraseña para {users.find((u) => u.id === resetId)?.username}
+                            </label>
+                            <input
+                                id="reset-password"
+                                type="password"
+                                className="form-input"
+                                value={resetPassword}
+                                onChange={(e) => setResetPassword(e.target.value)}
+                                autoFocus
+                            />
+                        </div>
+                        <button className="btn-save" onClick={handleResetPassword}>
+                            <FiCheck size={16} /> Guardar
+                        </button>
+                        <button className="btn-cancel-edit" onClick={() => setResetId(null)}>
+                            Cancelar
+                        </button>
+                    </div>
+                </div>
+            )}
+
+            {/* Users Table */}
+            <div className="employees-section">
+                {loading ? (
+                    <div className="loading">Cargando usuarios...</div>
+                ) : (
+                    <table className="employee-table">
+                        <thead>
+                            <tr>
+                                <th>Usuario</th>
+                                <th>Nombre</th>
+                                <th>Rol</th>
+                                <th>Estado</th>
+                                <th>Acciones</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {users.map((user) => (
+                                <tr key={user.id} className={!user.active ? "inactive" : ""}>
+                                    <td>{user.username}</td>
+                                    {editingId === user.id ? (
+                                        <>
+                                            <td>
+                                                <input
+                                                    type="text"
+                                                    className="edit-input"
+                                                    value={editDisplayName}
+                                                    onChange={(e) => setEditDisplayName(e.target.value)}
+                                                    autoFocus
+                                                />
+                                            </td>
+                                            <td>
+                                                <select
+                                                    className="edit-input"
+                                                    value={editRole}
+                                                    onChange={(e) => setEditRole(e.target.value as Role)}
+                                                >
+                                                    {ROLES.map((role) => (
+                                                        <option key={role} value={role}>
+                                                            {ROLE_LABELS[role]}
+                                                        </option>
+                                                    ))}
+                                                </select>
+                                            </td>
+                                        </>
+                                    ) : (
+                                        <>
+                                            <td>{user.display_name}</td>
+                                            <td>{ROLE_LABELS[user.role]}</td>
+                                        </>
+                                    )}
+                                    <td>
+                                        <span className={`status-badge ${user.active ? "active" : "inactive"}`}>
+                                            {user.active ? "Activo" : "Inactivo"}
+                                        </span>
+                                    </td>
+                                    <td className="actions-cell">
+                                        {editingId === user.id ? (
+                                            <>
+                                                <button
+                                                    className="btn-save"
+                                                    onClick={() => updateUser(user, editDisplayName, editRole, user.active)}
+                                                >
+                                                    Guardar
+                                                </button>
+                                                <button className="btn-cancel-edit" onClick={() => setEditingId(null)}>
+                                                    Cancelar
+                                                </button>
+                                            </>
+                                        ) : (
+                                            <>
+                                                <button
+                                                    className="btn-toggle"
+                                                    onClick={() => updateUser(user, user.display_name, user.role, !user.active)}
+                                                    title={user.active ? "Desactivar" : "Activar"}
+                                                >
+                                                    {user.active ? (
+                                                        <FiToggleRight size={20} />
+                                                    ) : (
+                                                        <FiToggleLeft size={20} />
+                                                    )}
+                                                </button>
+                                                <button
+                                                    className="btn-edit"
+                                                    onClick={() => setResetId(user.id)}
+                                                    title="Restablecer contraseña"
+                                                >
+                                                    <FiKey size={16} />
+                                                </button>
+                                                <button
+                                                    className="btn-edit"
+                                                    onClick={() => startEdit(user)}
+                                                    title="Editar"
+                                                >
+                                                    <FiEdit2 size={16} />
+                                                </button>
+                                            </>
+                                        )}
+                                    </td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                )}
+            </div>
+        </div>
+    );
+}
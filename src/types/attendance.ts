@@ -1,3 +1,5 @@
+import type { AppError } from "./errors";
+
 export interface AttendanceRecord {
 	id: number;
 	employee_id: string;
@@ -7,13 +9,24 @@ export interface AttendanceRecord {
 	notes: string | null;
 	created_at: string;
 	updated_at: string;
+	flag: "out_of_sequence" | "placeholder" | "clock_anomaly" | null;
+	late_minutes: number | null;
+	early_departure_minutes: number | null;
+	deleted_at: string | null;
+	deleted_by: string | null;
+	source: RecordSource;
+	device_id: string | null;
 }
 
+export type RecordSource = "kiosk" | "manual" | "import" | "auto_close" | "api";
+
 export interface RecordFilter {
 	start_date?: string;
 	end_date?: string;
 	employee_id?: string;
 	type?: "entry" | "exit";
+	source?: RecordSource;
+	device_id?: string;
 }
 
 export interface DailyStats {
@@ -21,6 +34,13 @@ export interface DailyStats {
 	total_exits: number;
 	unique_employees_present: number;
 	last_activity: AttendanceRecord | null;
+	absentees: Absentee[];
+}
+
+export interface Absentee {
+	employee_id: string;
+	employee_name: string;
+	expected_start: string;
 }
 
 export interface Employee {
@@ -29,4 +49,201 @@ export interface Employee {
 	active: boolean;
 	created_at: string;
 	updated_at: string;
+	deleted_at: string | null;
+	deleted_by: string | null;
+	has_pin: boolean;
+}
+
+export type PresenceState = "present" | "absent";
+
+export type SequencePolicy = "reject" | "warn" | "placeholder";
+
+export type Locale = "es-MX" | "en-US";
+
+export interface PunchOutcome {
+	message: string;
+	warning: string | null;
+	state: PresenceState;
+}
+
+export interface BadgePunch extends PunchOutcome {
+	employee_id: string;
+	employee_name: string;
+}
+
+export interface Badge {
+	code: string;
+	employee_id: string;
+	employee_name: string | null;
+	label: string | null;
+	created_at: string;
+}
+
+export interface PunchError extends AppError {
+	current_state: PresenceState | null;
+}
+
+export interface Shift {
+	entry_id: number;
+	exit_id: number;
+	start: string;
+	end: string;
+	worked_seconds: number;
+	needs_review: boolean;
+}
+
+export interface UnmatchedPunch {
+	record_id: number;
+	timestamp: string;
+	type: "entry" | "exit";
+	reason: "missing_exit" | "missing_entry" | "placeholder" | "invalid_timestamp";
+}
+
+export interface DayTotal {
+	date: string;
+	worked_seconds: number;
+	shifts: Shift[];
+}
+
+export interface EmployeeTimesheet {
+	employee_id: string;
+	employee_name: string | null;
+	total_seconds: number;
+	days: DayTotal[];
+	unmatched: UnmatchedPunch[];
+}
+
+export interface ScheduleDay {
+	weekday: number; // 0 = Monday
+	start_time: string;
+	end_time: string;
+}
+
+export interface WeeklySchedule {
+	employee_id: string;
+	effective_from: string;
+	days: ScheduleDay[];
+}
+
+export interface Deviation {
+	record_id: number;
+	employee_id: string;
+	employee_name: string | null;
+	workday: string;
+	kind: "late" | "early_departure";
+	expected: string;
+	actual: string;
+	minutes: number;
+}
+
+export interface TardinessSummary {
+	employee_id: string;
+	employee_name: string | null;
+	late_count: number;
+	late_minutes: number;
+	early_departure_count: number;
+	early_departure_minutes: number;
+	incidents: Deviation[];
+}
+
+export interface Holiday {
+	date: string;
+	name: string;
+}
+
+export interface EmployeeAbsences {
+	employee_id: string;
+	employee_name: string;
+	expected_days: number;
+	absent_days: string[];
+}
+
+export interface ClockAnomaly {
+	id: number;
+	kind: "backward" | "forward";
+	expected_at: string;
+	detected_at: string;
+	drift_seconds: number;
+	employee_id: string;
+	employee_name: string | null;
+	record_id: number | null;
+}
+
+export type Role = "owner" | "admin" | "supervisor" | "auditor";
+
+export type Permission =
+	| "view_records"
+	| "export_records"
+	| "edit_records"
+	| "delete_records"
+	| "manage_employees"
+	| "manage_settings"
+	| "manage_users"
+	| "view_audit";
+
+export interface SessionUser {
+	id: number;
+	username: string;
+	display_name: string;
+	role: Role;
+	permissions: Permission[];
+}
+
+export interface LoginResponse {
+	token: string;
+	user: SessionUser;
+}
+
+export interface UserAccount {
+	id: number;
+	username: string;
+	display_name: string;
+	role: Role;
+	active: boolean;
+	created_at: string;
+	updated_at: string;
+}
+
+export interface AuditEntry {
+	id: number;
+	timestamp: string;
+	actor: string;
+	command: string;
+	entity: "attendance" | "employee";
+	entity_id: string;
+	employee_id: string;
+	before: Record<string, unknown> | null;
+	after: Record<string, unknown> | null;
+	reason: string | null;
+}
+
+export interface RecycleBin {
+	records: AttendanceRecord[];
+	employees: Employee[];
+}
+
+export interface RecoveryStatus {
+	active: boolean;
+	locked: boolean;
+	problems: string[];
+	db_path: string;
+	latest_backup: string | null;
+	has_recovery_secret: boolean;
+	backup_error: string | null;
+}
+
+export type BackupKind = "startup" | "daily" | "manual" | "pre_migration" | "pre_bulk" | "pre_restore";
+
+export interface BackupInfo {
+	name: string;
+	path: string;
+	kind: BackupKind;
+	created_at: string;
+	size: number;
+}
+
+export interface BackupSettings {
+	dir: string;
+	keep_daily: number;
+	keep_weekly: number;
 }
@@ -0,0 +1,16 @@
+export type ErrorCode = "VALIDATION" | "NOT_FOUND" | "CONFLICT" | "AUTH" | "DATABASE" | "INTERNAL";
+
+export interface AppError {
+	code: ErrorCode;
+	message: string;
+	details: string | null;
+}
+
+export function isAppError(error: unknown): error is AppError {
+	return typeof error === "object" && error !== null && "code" in error && "message" in error;
+}
+
+/** Message to show for an error thrown by `invoke`. */
+export function describeError(error: unknown): string {
+	return isAppError(error) ? error.message : String(error);
+}